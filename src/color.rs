@@ -0,0 +1,302 @@
+/// Defines a color in the sRGB space, each channel between 0 and 1.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rgb {
+    pub red: f64,
+    pub green: f64,
+    pub blue: f64,
+}
+
+/// Lists the named colors understood besides the hexadecimal notations.
+const NAMED_COLORS: [(&str, u32); 20] = [
+    ("black", 0x000000),
+    ("white", 0xffffff),
+    ("gray", 0x808080),
+    ("grey", 0x808080),
+    ("silver", 0xc0c0c0),
+    ("red", 0xff0000),
+    ("maroon", 0x800000),
+    ("orange", 0xffa500),
+    ("gold", 0xffd700),
+    ("yellow", 0xffff00),
+    ("lime", 0x00ff00),
+    ("green", 0x008000),
+    ("teal", 0x008080),
+    ("cyan", 0x00ffff),
+    ("aqua", 0x00ffff),
+    ("blue", 0x0000ff),
+    ("navy", 0x000080),
+    ("purple", 0x800080),
+    ("magenta", 0xff00ff),
+    ("pink", 0xffc0cb),
+];
+
+/// Lists the color keywords of CSS, in lowercase: the named colors, then the special keywords.
+const CSS_COLOR_KEYWORDS: [&str; 152] = [
+    "aliceblue", "antiquewhite", "aqua", "aquamarine", "azure", "beige", "bisque", "black",
+    "blanchedalmond", "blue", "blueviolet", "brown", "burlywood", "cadetblue", "chartreuse",
+    "chocolate", "coral", "cornflowerblue", "cornsilk", "crimson", "cyan", "darkblue", "darkcyan",
+    "darkgoldenrod", "darkgray", "darkgreen", "darkgrey", "darkkhaki", "darkmagenta",
+    "darkolivegreen", "darkorange", "darkorchid", "darkred", "darksalmon", "darkseagreen",
+    "darkslateblue", "darkslategray", "darkslategrey", "darkturquoise", "darkviolet", "deeppink",
+    "deepskyblue", "dimgray", "dimgrey", "dodgerblue", "firebrick", "floralwhite", "forestgreen",
+    "fuchsia", "gainsboro", "ghostwhite", "gold", "goldenrod", "gray", "green", "greenyellow",
+    "grey", "honeydew", "hotpink", "indianred", "indigo", "ivory", "khaki", "lavender",
+    "lavenderblush", "lawngreen", "lemonchiffon", "lightblue", "lightcoral", "lightcyan",
+    "lightgoldenrodyellow", "lightgray", "lightgreen", "lightgrey", "lightpink", "lightsalmon",
+    "lightseagreen", "lightskyblue", "lightslategray", "lightslategrey", "lightsteelblue",
+    "lightyellow", "lime", "limegreen", "linen", "magenta", "maroon", "mediumaquamarine",
+    "mediumblue", "mediumorchid", "mediumpurple", "mediumseagreen", "mediumslateblue",
+    "mediumspringgreen", "mediumturquoise", "mediumvioletred", "midnightblue", "mintcream",
+    "mistyrose", "moccasin", "navajowhite", "navy", "oldlace", "olive", "olivedrab", "orange",
+    "orangered", "orchid", "palegoldenrod", "palegreen", "paleturquoise", "palevioletred",
+    "papayawhip", "peachpuff", "peru", "pink", "plum", "powderblue", "purple", "rebeccapurple",
+    "red", "rosybrown", "royalblue", "saddlebrown", "salmon", "sandybrown", "seagreen", "seashell",
+    "sienna", "silver", "skyblue", "slateblue", "slategray", "slategrey", "snow", "springgreen",
+    "steelblue", "tan", "teal", "thistle", "tomato", "turquoise", "violet", "wheat", "white",
+    "whitesmoke", "yellow", "yellowgreen", "transparent", "currentcolor", "context-stroke",
+    "context-fill",
+];
+
+impl Rgb {
+    /// Builds a color from a `0xrrggbb` integer.
+    fn from_hex(hex: u32) -> Self {
+        Rgb {
+            red: ((hex >> 16) & 0xff) as f64 / 255.0,
+            green: ((hex >> 8) & 0xff) as f64 / 255.0,
+            blue: (hex & 0xff) as f64 / 255.0,
+        }
+    }
+
+    /// Parses a color written as `#rgb`, `#rrggbb` or one of the common CSS names.
+    pub fn parse(color: &str) -> Option<Self> {
+        let color = color.trim();
+        let Some(digits) = color.strip_prefix('#') else {
+            let name = color.to_ascii_lowercase();
+            return NAMED_COLORS.iter().find(|(n, _)| *n == name).map(|(_, hex)| Rgb::from_hex(*hex));
+        };
+        if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        match digits.len() {
+            3 => {
+                // Each digit is doubled: `#fa0` is `#ffaa00`.
+                let hex = u32::from_str_radix(digits, 16).ok()?;
+                let (r, g, b) = ((hex >> 8) & 0xf, (hex >> 4) & 0xf, hex & 0xf);
+                Some(Rgb::from_hex((r * 0x11) << 16 | (g * 0x11) << 8 | (b * 0x11)))
+            }
+            6 => Some(Rgb::from_hex(u32::from_str_radix(digits, 16).ok()?)),
+            _ => None,
+        }
+    }
+
+    /// Formats the color as `#rrggbb`.
+    pub fn to_hex(self) -> String {
+        let channel = |c: f64| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+        format!("#{:02x}{:02x}{:02x}", channel(self.red), channel(self.green), channel(self.blue))
+    }
+
+    /// Computes the relative luminance of the color, as defined by WCAG.
+    pub fn luminance(self) -> f64 {
+        let linear = |c: f64| if c <= 0.03928 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) };
+        0.2126 * linear(self.red) + 0.7152 * linear(self.green) + 0.0722 * linear(self.blue)
+    }
+
+    /// Converts the color into hue (in turns), saturation and lightness.
+    fn to_hsl(self) -> (f64, f64, f64) {
+        let max = self.red.max(self.green).max(self.blue);
+        let min = self.red.min(self.green).min(self.blue);
+        let lightness = (max + min) / 2.0;
+        let delta = max - min;
+        if delta == 0.0 {
+            return (0.0, 0.0, lightness);
+        }
+
+        let saturation = delta / (1.0 - (2.0 * lightness - 1.0).abs());
+        let hue = if max == self.red {
+            ((self.green - self.blue) / delta).rem_euclid(6.0)
+        } else if max == self.green {
+            (self.blue - self.red) / delta + 2.0
+        } else {
+            (self.red - self.green) / delta + 4.0
+        };
+        (hue / 6.0, saturation, lightness)
+    }
+
+    /// Builds a color from its hue (in turns), saturation and lightness.
+    fn from_hsl(hue: f64, saturation: f64, lightness: f64) -> Self {
+        let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+        let sector = hue.rem_euclid(1.0) * 6.0;
+        let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+        let (r, g, b) = match sector as u32 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        let m = lightness - chroma / 2.0;
+        Rgb { red: r + m, green: g + m, blue: b + m }
+    }
+}
+
+/// Computes the WCAG contrast ratio between two colors, from 1 (none) to 21.
+pub fn contrast_ratio(a: Rgb, b: Rgb) -> f64 {
+    let (light, dark) = if a.luminance() > b.luminance() { (a, b) } else { (b, a) };
+    (light.luminance() + 0.05) / (dark.luminance() + 0.05)
+}
+
+/// Adjusts the lightness of a color until its contrast ratio against the background
+/// reaches `min_ratio`, brightening it on dark backgrounds and darkening it otherwise.
+/// Colors already contrasted enough, or that can't be parsed, are returned unchanged.
+/// Ratios that even white or black can't reach, e.g. on a mid-gray background, give
+/// white or black: the most contrasted color in that direction.
+pub fn ensure_contrast(color: &str, background: &str, min_ratio: f64) -> String {
+    let (Some(rgb), Some(back)) = (Rgb::parse(color), Rgb::parse(background)) else {
+        return color.to_string();
+    };
+    if contrast_ratio(rgb, back) >= min_ratio {
+        return color.to_string();
+    }
+
+    // Steps by hundredths of lightness, the last step landing exactly on white or black.
+    let (hue, saturation, lightness) = rgb.to_hsl();
+    let target = if back.luminance() < 0.5 { 1.0 } else { 0.0 };
+    let steps = ((target - lightness).abs() * 100.0).ceil() as u32;
+    let mut adjusted = rgb;
+    for k in 1..=steps {
+        let lightness = if k == steps { target } else { lightness + (target - lightness).signum() * k as f64 / 100.0 };
+        adjusted = Rgb::from_hsl(hue, saturation, lightness);
+        if contrast_ratio(adjusted, back) >= min_ratio {
+            break;
+        }
+    }
+    adjusted.to_hex()
+}
+
+/// Turns the hue of a color by the given angle, in degrees, keeping its saturation and lightness.
+/// Colors that can't be parsed are returned unchanged.
+pub fn shift_hue(color: &str, degrees: f64) -> String {
+    match Rgb::parse(color) {
+        Some(rgb) => {
+            let (hue, saturation, lightness) = rgb.to_hsl();
+            Rgb::from_hsl(hue + degrees / 360.0, saturation, lightness).to_hex()
+        }
+        None => color.to_string(),
+    }
+}
+
+/// Blends two colors channel by channel, `t` going from 0 for `start` to 1 for `end`.
+/// Colors that can't be parsed don't blend: the nearest of the two is returned unchanged.
+pub fn mix(start: &str, end: &str, t: f64) -> String {
+    match (Rgb::parse(start), Rgb::parse(end)) {
+        (Some(a), Some(b)) => {
+            let blend = |from: f64, to: f64| from + (to - from) * t;
+            Rgb { red: blend(a.red, b.red), green: blend(a.green, b.green), blue: blend(a.blue, b.blue) }.to_hex()
+        }
+        _ if t < 0.5 => start.to_string(),
+        _ => end.to_string(),
+    }
+}
+
+/// Checks that a value can be used as an SVG stroke color: a hexadecimal color,
+/// a CSS color name, an `rgb()`/`hsl()` function, `currentColor`, `context-stroke`,
+/// or a CSS variable such as `var(--curve-color, cyan)`.
+///
+/// A CSS variable lets the page embedding the SVG recolor its curves without
+/// regenerating it, e.g. with `svg { --curve-color: gold; }`. The fallback after the
+/// comma applies wherever the variable isn't set, including in standalone viewers.
+/// The variable is emitted verbatim, so `ensure_contrast` leaves such colors as they are.
+pub fn is_css_color(value: &str) -> bool {
+    let value = value.trim();
+    if Rgb::parse(value).is_some() {
+        return true;
+    }
+    if let Some(arguments) = value.strip_prefix("var(").and_then(|rest| rest.strip_suffix(')')) {
+        let (name, fallback) = match arguments.split_once(',') {
+            Some((name, fallback)) => (name.trim(), Some(fallback)),
+            None => (arguments.trim(), None),
+        };
+        let valid_name = name.len() > 2
+            && name.starts_with("--")
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        return valid_name && fallback.is_none_or(is_css_color);
+    }
+    let arguments = ["rgb(", "rgba(", "hsl(", "hsla("].iter().find_map(|prefix| value.strip_prefix(prefix));
+    if let Some(arguments) = arguments {
+        // Numbers, units and separators only, so the color can't break out of its attribute.
+        return arguments.strip_suffix(')').is_some_and(|arguments| {
+            arguments.chars().all(|c| c.is_ascii_alphanumeric() || " ,.%/+-".contains(c))
+        });
+    }
+    let keyword = value.to_ascii_lowercase();
+    CSS_COLOR_KEYWORDS.contains(&keyword.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colors_parse_from_hex_and_names() {
+        assert_eq!(Rgb::parse("#fa0"), Rgb::parse("#ffaa00"));
+        assert_eq!(Rgb::parse(" Gold ").map(Rgb::to_hex).as_deref(), Some("#ffd700"));
+        assert_eq!(Rgb::parse("#1a2B3c").map(Rgb::to_hex).as_deref(), Some("#1a2b3c"));
+        for invalid in ["#12", "#ggg", "#1234567", "nope", ""] {
+            assert_eq!(Rgb::parse(invalid), None, "{:?}", invalid);
+        }
+    }
+
+    #[test]
+    fn contrast_ratios_go_from_1_to_21() {
+        let (black, white) = (Rgb::parse("black").unwrap(), Rgb::parse("white").unwrap());
+        assert!((contrast_ratio(black, white) - 21.0).abs() < 1e-9);
+        assert_eq!(contrast_ratio(white, black), contrast_ratio(black, white));
+        assert_eq!(contrast_ratio(black, black), 1.0);
+    }
+
+    #[test]
+    fn ensured_contrast_meets_the_ratio() {
+        for (color, background) in [("#222", "#111"), ("navy", "black"), ("#eee", "#fff"), ("yellow", "white")] {
+            let adjusted = ensure_contrast(color, background, 4.5);
+            let ratio = contrast_ratio(Rgb::parse(&adjusted).unwrap(), Rgb::parse(background).unwrap());
+            assert!(ratio >= 4.5, "{} on {} only reaches {}", adjusted, background, ratio);
+        }
+
+        // Only white itself is contrasted enough, and the last step lands on it.
+        assert_eq!(ensure_contrast("navy", "black", 20.9), "#ffffff");
+        assert_eq!(ensure_contrast("gold", "white", 20.9), "#000000");
+
+        assert_eq!(ensure_contrast("white", "black", 4.5), "white");
+        assert_eq!(ensure_contrast("var(--curve-color)", "black", 4.5), "var(--curve-color)");
+    }
+
+    #[test]
+    fn hue_shifts_keep_saturation_and_lightness() {
+        assert_eq!(shift_hue("red", 120.0), "#00ff00");
+        assert_eq!(shift_hue("red", -120.0), "#0000ff");
+        assert_eq!(shift_hue("#ff0000", 360.0), "#ff0000");
+        assert_eq!(shift_hue("gray", 90.0), "#808080");
+        assert_eq!(shift_hue("var(--x)", 90.0), "var(--x)");
+    }
+
+    #[test]
+    fn css_colors_include_variables_and_functions() {
+        for color in ["var(--accent)", "var(--a, red)", "var(--a, var(--b, #fff))", "rgb(1,2,3)", "rgba(1, 2, 3, 50%)", "hsl(120 50% 50% / 0.5)", "currentColor", "context-stroke", "LightGoldenrodYellow", "#0ff"] {
+            assert!(is_css_color(color), "{} is rejected", color);
+        }
+        for color in ["not-a-color", "var(", "var(--accent", "var(accent)", "var(--a, nope)", "rgb(1,2,3", "rgb(1\" onload=\"x)", "url(#x)", ""] {
+            assert!(!is_css_color(color), "{} is accepted", color);
+        }
+    }
+
+    #[test]
+    fn mixes_blend_channel_by_channel() {
+        assert_eq!(mix("black", "white", 0.0), "#000000");
+        assert_eq!(mix("black", "white", 0.5), "#808080");
+        assert_eq!(mix("black", "white", 1.0), "#ffffff");
+        assert_eq!(mix("red", "blue", 0.25), "#bf0040");
+        assert_eq!(mix("var(--a)", "red", 0.2), "var(--a)");
+        assert_eq!(mix("var(--a)", "red", 0.8), "red");
+    }
+}
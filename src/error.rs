@@ -0,0 +1,44 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+/// Describes everything that can go wrong while rendering rosettas.
+#[derive(Debug)]
+pub enum RosettaError {
+    Io(io::Error),                                 // The output couldn't be written.
+    UnknownStyle(String),                          // A scene refers to a style missing from the registry.
+    InvalidStyle(String),                          // A style holds a value that can't be rendered.
+    InvalidCurve(String),                          // The parameters of a curve describe a degenerate shape.
+    Raster(String),                                // The scene couldn't be rasterized into an image.
+    Config(String),                                // A scene file couldn't be parsed.
+    InvalidField { field: String, reason: String }, // A scene field holds an out-of-range value.
+}
+
+impl fmt::Display for RosettaError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RosettaError::Io(e) => write!(f, "{}", e),
+            RosettaError::UnknownStyle(name) => write!(f, "unknown style '{}'", name),
+            RosettaError::InvalidStyle(message) => write!(f, "invalid style: {}", message),
+            RosettaError::InvalidCurve(message) => write!(f, "invalid curve: {}", message),
+            RosettaError::Raster(message) => write!(f, "rasterization failed: {}", message),
+            RosettaError::Config(message) => write!(f, "invalid scene file: {}", message),
+            RosettaError::InvalidField { field, reason } => write!(f, "{} {}", field, reason),
+        }
+    }
+}
+
+impl Error for RosettaError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            RosettaError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for RosettaError {
+    fn from(e: io::Error) -> Self {
+        RosettaError::Io(e)
+    }
+}
@@ -0,0 +1,312 @@
+use crate::rosetta::Coordinate;
+use alloc::vec;
+use alloc::vec::Vec;
+#[cfg(not(any(feature = "std", test)))]
+use num_traits::Float;
+
+// An axis-aligned rectangle enclosing a set of coordinates.
+#[derive(Debug, Clone, Copy)]
+pub struct BoundingBox {
+    pub min: Coordinate, // Lower-left corner of the box.
+    pub max: Coordinate, // Upper-right corner of the box.
+}
+
+impl BoundingBox {
+    /// Box enclosing no point, which any point or box grows.
+    pub(crate) const EMPTY: BoundingBox = BoundingBox {
+        min: Coordinate { x: f64::MAX, y: f64::MAX },
+        max: Coordinate { x: f64::MIN, y: f64::MIN },
+    };
+
+    /// Computes the smallest box enclosing all the given points.
+    pub fn from_points(points: &[Coordinate]) -> Self {
+        points.iter().copied().collect()
+    }
+
+    /// Returns the horizontal extent of the box.
+    pub fn width(&self) -> f64 {
+        self.max.x - self.min.x
+    }
+
+    /// Returns the vertical extent of the box.
+    pub fn height(&self) -> f64 {
+        self.max.y - self.min.y
+    }
+
+    /// Returns the smallest box enclosing both this box and point `p`.
+    pub fn including(self, p: Coordinate) -> Self {
+        BoundingBox {
+            min: Coordinate { x: self.min.x.min(p.x), y: self.min.y.min(p.y) },
+            max: Coordinate { x: self.max.x.max(p.x), y: self.max.y.max(p.y) },
+        }
+    }
+
+    /// Returns the smallest box enclosing both boxes.
+    pub fn union(self, other: BoundingBox) -> Self {
+        self.including(other.min).including(other.max)
+    }
+
+    /// Returns the center point of the box.
+    pub fn center(&self) -> Coordinate {
+        Coordinate {
+            x: (self.max.x + self.min.x) / 2.0,
+            y: (self.max.y + self.min.y) / 2.0,
+        }
+    }
+}
+
+impl FromIterator<Coordinate> for BoundingBox {
+    /// Computes the smallest box enclosing the points as they come, without storing them.
+    fn from_iter<I: IntoIterator<Item = Coordinate>>(points: I) -> Self {
+        points.into_iter().fold(BoundingBox::EMPTY, BoundingBox::including)
+    }
+}
+
+/// Computes the length of the polyline joining the points in order.
+pub fn polyline_length(points: &[Coordinate]) -> f64 {
+    points
+        .windows(2)
+        .map(|pair| (pair[1].x - pair[0].x).hypot(pair[1].y - pair[0].y))
+        .sum()
+}
+
+/// Computes the arithmetic mean of the points.
+/// For a recentered curve, this lands close to the origin.
+pub fn centroid(points: &[Coordinate]) -> Coordinate {
+    let count = points.len().max(1) as f64;
+    let (sum_x, sum_y) = points.iter().fold((0.0, 0.0), |(x, y), p| (x + p.x, y + p.y));
+    Coordinate { x: sum_x / count, y: sum_y / count }
+}
+
+/// Computes the area-weighted centroid of the closed polygon formed by the points,
+/// using the shoelace formula. The last point is implicitly joined to the first.
+/// Unlike `centroid`, this generally isn't at the origin of a recentered curve.
+/// Falls back to the arithmetic mean when the polygon has no area.
+pub fn polygon_centroid(points: &[Coordinate]) -> Coordinate {
+    let area = signed_area(points);
+    let (mut sum_x, mut sum_y) = (0.0, 0.0);
+    for (p, q) in closed_edges(points) {
+        let cross = p.x * q.y - q.x * p.y;
+        sum_x += (p.x + q.x) * cross;
+        sum_y += (p.y + q.y) * cross;
+    }
+
+    if area.abs() < f64::EPSILON {
+        return centroid(points);
+    }
+    Coordinate { x: sum_x / (6.0 * area), y: sum_y / (6.0 * area) }
+}
+
+/// Iterates over the edges of the closed polygon formed by the points,
+/// including the one joining the last point back to the first.
+fn closed_edges(points: &[Coordinate]) -> impl Iterator<Item = (Coordinate, Coordinate)> + '_ {
+    points
+        .iter()
+        .enumerate()
+        .map(|(i, p)| (*p, points[(i + 1) % points.len()]))
+}
+
+/// Computes the signed area of the closed polygon formed by the points, using the
+/// shoelace formula. It's positive when the points turn counterclockwise.
+/// Loops turning in opposite directions cancel each other out.
+pub fn signed_area(points: &[Coordinate]) -> f64 {
+    closed_edges(points).map(|(p, q)| p.x * q.y - q.x * p.y).sum::<f64>() / 2.0
+}
+
+/// Computes the points where the polyline crosses itself.
+/// Every pair of non-adjacent segments is tested, so this is quadratic in the number of points.
+pub fn self_intersections(points: &[Coordinate]) -> Vec<Coordinate> {
+    let segments = points.len().saturating_sub(1);
+    let mut crossings = Vec::new();
+    for i in 0..segments {
+        for j in (i + 2)..segments {
+            // Like adjacent segments, the first and last ones meet on a closed curve.
+            if i == 0 && j == segments - 1 {
+                continue;
+            }
+            if let Some(p) = segment_intersection(points[i], points[i + 1], points[j], points[j + 1]) {
+                crossings.push(p);
+            }
+        }
+    }
+    crossings
+}
+
+/// Computes the point where the segments `[a, b]` and `[c, d]` cross, if they do.
+fn segment_intersection(a: Coordinate, b: Coordinate, c: Coordinate, d: Coordinate) -> Option<Coordinate> {
+    let (ab_x, ab_y) = (b.x - a.x, b.y - a.y);
+    let (cd_x, cd_y) = (d.x - c.x, d.y - c.y);
+    let denominator = ab_x * cd_y - ab_y * cd_x;
+    if denominator == 0.0 {
+        return None; // Parallel segments.
+    }
+
+    let t = ((c.x - a.x) * cd_y - (c.y - a.y) * cd_x) / denominator;
+    let u = ((c.x - a.x) * ab_y - (c.y - a.y) * ab_x) / denominator;
+    ((0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u))
+        .then_some(Coordinate { x: a.x + t * ab_x, y: a.y + t * ab_y })
+}
+
+/// Simplifies the polyline with the Ramer-Douglas-Peucker algorithm, dropping the
+/// points closer than `tolerance` to the chord joining their kept neighbours.
+/// The first and last points are always kept.
+pub fn simplify(points: &[Coordinate], tolerance: f64) -> Vec<Coordinate> {
+    if points.len() < 3 {
+        return points.to_vec();
+    }
+
+    let mut keep = vec![false; points.len()];
+    keep[0] = true;
+    keep[points.len() - 1] = true;
+
+    // Splits the ranges on their farthest point, until every point is close enough.
+    let mut ranges = vec![(0, points.len() - 1)];
+    while let Some((first, last)) = ranges.pop() {
+        let farthest = (first + 1..last)
+            .map(|i| (i, distance_to_segment(points[i], points[first], points[last])))
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        if let Some((i, distance)) = farthest
+            && distance > tolerance
+        {
+            keep[i] = true;
+            ranges.push((first, i));
+            ranges.push((i, last));
+        }
+    }
+
+    points.iter().zip(keep).filter(|(_, kept)| *kept).map(|(p, _)| *p).collect()
+}
+
+/// Snaps every point to the nearest multiple of `step` on both axes, e.g. to the
+/// integer pixel grid with a step of 1. Large steps facet the curve visibly, as
+/// neighboring points collapse onto the same grid node or jump between nodes.
+/// A step that isn't a positive number leaves the points as they are.
+pub fn quantize(points: &[Coordinate], step: f64) -> Vec<Coordinate> {
+    if !(step.is_finite() && step > 0.0) {
+        return points.to_vec();
+    }
+    points
+        .iter()
+        .map(|p| Coordinate {
+            x: (p.x / step).round() * step,
+            y: (p.y / step).round() * step,
+        })
+        .collect()
+}
+
+/// Computes the distance from a point to the segment `[a, b]`.
+pub(crate) fn distance_to_segment(p: Coordinate, a: Coordinate, b: Coordinate) -> f64 {
+    let (ab_x, ab_y) = (b.x - a.x, b.y - a.y);
+    let length_squared = ab_x * ab_x + ab_y * ab_y;
+    let t = if length_squared == 0.0 {
+        0.0 // The segment is a single point, e.g. the seam of a closed curve.
+    } else {
+        (((p.x - a.x) * ab_x + (p.y - a.y) * ab_y) / length_squared).clamp(0.0, 1.0)
+    };
+    (p.x - (a.x + t * ab_x)).hypot(p.y - (a.y + t * ab_y))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rosetta::{Curve, Epicyclic, Hypotrochoid};
+
+    /// Builds a coordinate from its two components.
+    fn point(x: f64, y: f64) -> Coordinate {
+        Coordinate { x, y }
+    }
+
+    #[test]
+    fn centroids_of_simple_shapes() {
+        let square = [point(0.0, 0.0), point(2.0, 0.0), point(2.0, 2.0), point(0.0, 2.0)];
+        assert_eq!(centroid(&square), point(1.0, 1.0));
+        assert_eq!(polygon_centroid(&square), point(1.0, 1.0));
+
+        // Extra points along one edge pull the mean towards it, not the centroid of the area.
+        let crowded = [point(0.0, 0.0), point(0.5, 0.0), point(1.0, 0.0), point(1.5, 0.0), point(2.0, 0.0), point(2.0, 2.0), point(0.0, 2.0)];
+        assert!(centroid(&crowded).y < 1.0);
+        let area_centroid = polygon_centroid(&crowded);
+        assert!((area_centroid.x - 1.0).abs() < 1e-12 && (area_centroid.y - 1.0).abs() < 1e-12);
+
+        let triangle = [point(0.0, 0.0), point(3.0, 0.0), point(0.0, 3.0)];
+        assert_eq!(polygon_centroid(&triangle), point(1.0, 1.0));
+
+        // Without area, the polygon centroid falls back to the mean.
+        let segment = [point(0.0, 0.0), point(4.0, 0.0)];
+        assert_eq!(polygon_centroid(&segment), point(2.0, 0.0));
+        assert_eq!(centroid(&[]), point(0.0, 0.0));
+    }
+
+    #[test]
+    fn centroids_of_recentered_curves() {
+        // The mean of a rosetta lies near its center of symmetry, which the sampling moves
+        // slightly away from the center of its bounding box.
+        let rosetta = Hypotrochoid::default().compute_points();
+        let (center, width) = (centroid(&rosetta), BoundingBox::from_points(&rosetta).width());
+        assert!(center.x.hypot(center.y) < 1e-3 * width, "{:?}", center);
+
+        // A limaçon is centered on its bounding box, but most of its area lies on one side.
+        let limacon = Epicyclic { radii: vec![100.0, 50.0], rates: vec![1.0, 2.0], steps: 1000 }.compute_points();
+        let center = polygon_centroid(&limacon);
+        assert!(center.x.abs() > 1.0, "{:?}", center);
+    }
+
+    #[test]
+    fn quantized_points_sit_on_the_grid() {
+        let points = Hypotrochoid::default().with_steps(500).compute_points();
+        for step in [0.5, 1.0, 2.5, 10.0] {
+            let quantized = quantize(&points, step);
+            assert_eq!(quantized.len(), points.len());
+            for (p, q) in points.iter().zip(&quantized) {
+                for (value, snapped) in [(p.x, q.x), (p.y, q.y)] {
+                    assert!(((snapped / step).round() * step - snapped).abs() < 1e-9, "{} isn't a multiple of {}", snapped, step);
+                    assert!((snapped - value).abs() <= step / 2.0 + 1e-9);
+                }
+            }
+        }
+        assert_eq!(quantize(&points, 0.0), points);
+        assert_eq!(quantize(&points, f64::NAN), points);
+    }
+
+    #[test]
+    fn simplify_drops_collinear_points() {
+        let staircase = [point(0.0, 0.0), point(1.0, 0.0), point(2.0, 0.0), point(3.0, 0.0), point(3.0, 1.0), point(3.0, 2.0)];
+        assert_eq!(simplify(&staircase, 1e-9), [point(0.0, 0.0), point(3.0, 0.0), point(3.0, 2.0)]);
+
+        // A point off the chord is kept only when it strays further than the tolerance.
+        let bump = [point(0.0, 0.0), point(1.0, 0.05), point(2.0, 0.0)];
+        assert_eq!(simplify(&bump, 0.1), [point(0.0, 0.0), point(2.0, 0.0)]);
+        assert_eq!(simplify(&bump, 0.01), bump);
+        assert_eq!(simplify(&bump[..2], 10.0), bump[..2]);
+    }
+
+    #[test]
+    fn signed_areas_follow_the_winding() {
+        let square = [point(0.0, 0.0), point(2.0, 0.0), point(2.0, 2.0), point(0.0, 2.0)];
+        assert_eq!(signed_area(&square), 4.0);
+        let clockwise: Vec<Coordinate> = square.iter().rev().copied().collect();
+        assert_eq!(signed_area(&clockwise), -4.0);
+        let bowtie = [point(0.0, 0.0), point(2.0, 2.0), point(2.0, 0.0), point(0.0, 2.0)];
+        assert_eq!(signed_area(&bowtie), 0.0);
+    }
+
+    #[test]
+    fn figure_eights_cross_themselves_once() {
+        let bowtie = [point(0.0, 0.0), point(2.0, 2.0), point(2.0, 0.0), point(0.0, 2.0), point(0.0, 0.0)];
+        assert_eq!(self_intersections(&bowtie), [point(1.0, 1.0)]);
+        let square = [point(0.0, 0.0), point(2.0, 0.0), point(2.0, 2.0), point(0.0, 2.0), point(0.0, 0.0)];
+        assert!(self_intersections(&square).is_empty());
+
+        // A lemniscate of Gerono, sampled off its crossing and closed on its first point.
+        let mut lemniscate: Vec<Coordinate> = (0..101)
+            .map(|k| {
+                let (sin, cos) = (core::f64::consts::TAU * (k as f64 + 0.5) / 101.0).sin_cos();
+                point(sin, sin * cos)
+            })
+            .collect();
+        lemniscate.push(lemniscate[0]);
+        let crossings = self_intersections(&lemniscate);
+        assert_eq!(crossings.len(), 1);
+        assert!(crossings[0].x.abs() < 1e-9 && crossings[0].y.abs() < 1e-9, "{:?}", crossings[0]);
+    }
+}
@@ -0,0 +1,37 @@
+//! Curve math and SVG rendering for the animated rosettas.
+//!
+//! The `rosetta` module describes the curves and computes their points, while
+//! the `renderer` module turns them into an animated SVG document. Scenes
+//! composed of several placed curves are described in the `scene` module.
+//! Polyline helpers shared by every curve type live in the `geometry` module,
+//! and color parsing and contrast helpers in the `color` module.
+//! With the `png` feature, the `raster` module renders scenes as images, and with
+//! the `rayon` feature, curves with many steps are sampled on every core.
+//!
+//! Only the curve math needs no operating system: without the default `std`
+//! feature, the crate is `no_std` (with `alloc`) and exposes the `rosetta` and
+//! `geometry` modules alone. Such builds enable the `core` feature instead,
+//! which computes the floating-point functions in software.
+//!
+//! The default `cli` feature builds the `rustigraph` binary and its logger; libraries
+//! depending on the crate can leave it out, keeping `std`, to skip building the logger.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(not(any(feature = "std", feature = "core")))]
+compile_error!("either the `std` or the `core` feature must be enabled");
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
+pub mod color;
+#[cfg(feature = "std")]
+pub mod error;
+pub mod geometry;
+pub mod rosetta;
+#[cfg(feature = "std")]
+pub mod renderer;
+#[cfg(feature = "std")]
+pub mod scene;
+#[cfg(feature = "png")]
+pub mod raster;
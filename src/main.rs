@@ -9,23 +9,156 @@
 //! This version is not just a direct translation; it includes several improvements:
 //! - Cleaner SVG output file generation.
 //! - Additional fun graphical effects, powered by CSS.
+//! - The number of revolutions of each hypotrochoid curve is calculated from its
+//!   parameters, so that it closes without being drawn over itself.
 //!
 //! ## Future Work
 //!
 //! To further improve the codebase, future versions could:
-//! - Calculate the number of revolutions for the hypotrochoid curve based on its
-//!   parameters, instead of using a hard-coded value.
 //! - Integrate the `indoc` crate to manage multi-line raw strings more cleanly.
 //! - Use the `svg` crate to build the SVG document programmatically instead of
 //!   using raw strings, which would make the code more robust and maintainable.
 
-mod rosetta;
-mod renderer;
+use rustigraph::error::RosettaError;
+use rustigraph::renderer::{self, Animation, Geometry, Pen, RenderOptions, RosettaStyle};
+use rustigraph::scene::{Scene, StyleRegistry};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::process::ExitCode;
+
+/// Describes the command line arguments.
+const USAGE: &str = "usage: rustigraph [--output <path>] [--steps <n>] [--rosetta R,r,d,color,duration]... [--stats]";
+
+/// Holds the settings given on the command line.
+struct Args {
+    output: String,              // File the SVG document is written to.
+    steps: Option<usize>,        // Number of steps overriding the one of every curve.
+    rosettas: Vec<RosettaStyle>, // Curves given with `--rosetta`, replacing the predefined ones.
+    stats: bool,                 // Prints how each curve is sampled and whether it closes.
+}
+
+/// Parses the command line arguments, the program name excluded.
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let mut parsed = Args { output: String::from("rosettas.svg"), steps: None, rosettas: Vec::new(), stats: false };
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("missing value after '{}'", arg));
+        match arg.as_str() {
+            "--output" => parsed.output = value()?,
+            "--steps" => {
+                let steps = value()?;
+                match steps.parse::<usize>() {
+                    Ok(steps) if steps > 0 => parsed.steps = Some(steps),
+                    _ => return Err(format!("invalid --steps '{}', expected a positive integer", steps)),
+                }
+            }
+            "--rosetta" => parsed.rosettas.push(parse_rosetta(&value()?)?),
+            "--stats" => parsed.stats = true,
+            _ => return Err(format!("unknown argument '{}'", arg)),
+        }
+    }
+    Ok(parsed)
+}
+
+/// Parses a curve given as `R,r,d,color,duration`, e.g. `150,52.5,97.5,cyan,6s`.
+fn parse_rosetta(value: &str) -> Result<RosettaStyle, String> {
+    let fields: Vec<&str> = value.split(',').map(str::trim).collect();
+    let [outer_radius, inner_radius, distance, color, duration] = fields[..] else {
+        return Err(format!("invalid --rosetta '{}', expected R,r,d,color,duration", value));
+    };
+    let number = |name: &str, field: &str| {
+        field
+            .parse::<f64>()
+            .map_err(|_| format!("invalid {} '{}' in --rosetta '{}', expected a number", name, field, value))
+    };
+    Ok(RosettaStyle {
+        geometry: Geometry {
+            outer_radius: number("outer radius", outer_radius)?,
+            inner_radius: number("inner radius", inner_radius)?,
+            distance: number("pen distance", distance)?,
+            ..Default::default()
+        },
+        pen: Pen { color: color.to_string(), ..Default::default() },
+        animation: Animation { duration: duration.to_string(), ..Default::default() },
+    })
+}
 
 /// The main function and entry point of the program.
-fn main() {
-    // Renders a predefined set of animated rosettas into a SVG output.
-    if let Err(e) = renderer::create_svg_rosettas() {
+fn main() -> ExitCode {
+    // Only warnings are shown by default; set `RUST_LOG=debug` for verbose output
+    // or `RUST_LOG=off` to silence the program entirely.
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn")).init();
+
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("ERROR: {}\n{}", e, USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    // Without any `--rosetta`, the predefined set of animated rosettas is rendered.
+    let mut styles = if args.rosettas.is_empty() { renderer::default_styles() } else { args.rosettas };
+    if let Some(steps) = args.steps {
+        styles.iter_mut().for_each(|style| style.geometry.steps = Some(steps));
+    }
+    let registry = StyleRegistry::default();
+    let scene = Scene {
+        options: RenderOptions::default(),
+        curves: styles.into_iter().map(Into::into).collect(),
+    };
+    if let Err(e) = scene.validate() {
+        eprintln!("ERROR: {}", e);
+        return ExitCode::FAILURE;
+    }
+
+    // Prints how each rosetta is sampled and whether it closes.
+    if args.stats {
+        for (i, curve) in scene.curves.iter().filter_map(|curve| curve.style.resolve(&registry).and_then(RosettaStyle::curve).ok()).enumerate() {
+            println!("rosetta {}: {}", i, curve.stats());
+        }
+    }
+
+    // Renders the animated rosettas into a SVG output, flushed so that a full disk is reported.
+    let written = File::create(&args.output).map_err(RosettaError::from).and_then(|file| {
+        let mut writer = BufWriter::new(file);
+        renderer::write_scene(&mut writer, &scene, &registry)?;
+        writer.flush().map_err(RosettaError::from)
+    });
+    if let Err(e) = written {
         eprintln!("ERROR: Failed to generate SVG file: {}", e);
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parses the given arguments, returning the error message if they're rejected.
+    fn parse_error(args: &[&str]) -> Option<String> {
+        parse_args(args.iter().map(|arg| arg.to_string())).err()
+    }
+
+    #[test]
+    fn arguments_override_the_defaults() {
+        let args = parse_args(["--output", "out.svg", "--steps", "500", "--rosetta", "150, 52.5, 97.5, cyan, 6s", "--stats"].into_iter().map(String::from)).unwrap();
+        assert_eq!((args.output.as_str(), args.steps, args.stats), ("out.svg", Some(500), true));
+        assert_eq!(args.rosettas.len(), 1);
+        assert_eq!(args.rosettas[0].geometry.inner_radius, 52.5);
+        assert_eq!(args.rosettas[0].animation.duration, "6s");
+
+        let args = parse_args(std::iter::empty()).unwrap();
+        assert_eq!((args.output.as_str(), args.steps, args.rosettas.len()), ("rosettas.svg", None, 0));
+    }
+
+    #[test]
+    fn invalid_arguments_are_reported() {
+        assert_eq!(parse_error(&["--rosetta", "150,52.5,cyan,6s"]).unwrap(), "invalid --rosetta '150,52.5,cyan,6s', expected R,r,d,color,duration");
+        assert_eq!(parse_error(&["--rosetta", "150,abc,97.5,cyan,6s"]).unwrap(), "invalid inner radius 'abc' in --rosetta '150,abc,97.5,cyan,6s', expected a number");
+        assert_eq!(parse_error(&["--output"]).unwrap(), "missing value after '--output'");
+        assert_eq!(parse_error(&["--steps", "0"]).unwrap(), "invalid --steps '0', expected a positive integer");
+        assert_eq!(parse_error(&["--steps", "-3"]).unwrap(), "invalid --steps '-3', expected a positive integer");
+        assert_eq!(parse_error(&["--verbose"]).unwrap(), "unknown argument '--verbose'");
     }
 }
@@ -0,0 +1,117 @@
+use crate::error::RosettaError;
+use crate::renderer::{centered_scene, fitted_scene, loop_duration, write_frame, RenderOptions, RosettaStyle};
+use crate::scene::{Scene, StyleRegistry};
+use log::debug;
+use resvg::tiny_skia::{Pixmap, Transform};
+use resvg::usvg;
+use std::fs;
+use std::path::Path;
+
+/// Rasterizes an SVG document into a pixmap of the given size.
+fn rasterize(svg: &[u8], width: u32, height: u32) -> Result<Pixmap, RosettaError> {
+    let tree = usvg::Tree::from_data(svg, &usvg::Options::default())
+        .map_err(|e| RosettaError::Raster(e.to_string()))?;
+    let mut pixmap = Pixmap::new(width, height)
+        .ok_or_else(|| RosettaError::Raster(format!("invalid image size {}x{}", width, height)))?;
+
+    let size = tree.size();
+    let transform = Transform::from_scale(width as f32 / size.width(), height as f32 / size.height());
+    resvg::render(&tree, transform, &mut pixmap.as_mut());
+    Ok(pixmap)
+}
+
+/// Rasterizes an SVG document into a PNG image of the given size, written to `path`.
+fn save_png(svg: &[u8], width: u32, height: u32, path: &Path) -> Result<(), RosettaError> {
+    rasterize(svg, width, height)?
+        .save_png(path)
+        .map_err(|e| RosettaError::Raster(e.to_string()))
+}
+
+/// Rasterizes rosettas, all centered on the canvas, into a PNG image of the given size.
+/// The image is a still frame of the document written by `render_svg`, with every curve
+/// at its starting angle: the background, grid and glow show, but nothing moves.
+///
+/// The canvas takes the proportions of the image, so curves are scaled evenly rather than
+/// stretched: the default canvas fits inside it, widened or heightened to the image's shape.
+pub fn render_png(styles: &[RosettaStyle], width: u32, height: u32, path: &Path) -> Result<(), RosettaError> {
+    let options = canvas_options(width, height);
+    let mut svg = Vec::new();
+    write_frame(&mut svg, &centered_scene(styles, &options), &StyleRegistry::default(), 0.0)?;
+    save_png(&svg, width, height, path)?;
+    debug!("wrote {} rosettas to {}", styles.len(), path.display());
+    Ok(())
+}
+
+/// Builds the default options on a canvas with the proportions of a `width` by `height` image,
+/// enclosing the default canvas.
+fn canvas_options(width: u32, height: u32) -> RenderOptions {
+    let default = RenderOptions::default();
+    let scale = (width as f64 / default.width as f64).min(height as f64 / default.height as f64);
+    if scale == 0.0 {
+        return default; // The image size is rejected when rasterizing.
+    }
+    RenderOptions {
+        width: (width as f64 / scale).round() as u32,
+        height: (height as f64 / scale).round() as u32,
+        ..default
+    }
+}
+
+/// Writes the rotation of the scene as numbered PNG images, `frame_0000.png` onwards.
+///
+/// The frames are evenly spaced over the loop of the scene, i.e. the least common
+/// multiple of the curves' durations (84s for durations of 4s, 6s and 14s), so the
+/// sequence wraps around seamlessly. To play it back at `fps` frames per second,
+/// request `loop * fps` frames. Curves with a start delay only join the loop once
+/// their delay has elapsed.
+pub fn render_frame_sequence(scene: &Scene, registry: &StyleRegistry, frames: usize, dir: &Path) -> Result<(), RosettaError> {
+    fs::create_dir_all(dir)?;
+    let scene = &*fitted_scene(scene, registry)?;
+    let loop_duration = loop_duration(scene, registry)?;
+    debug!("rendering {} frames over a {}s loop", frames, loop_duration);
+
+    for frame in 0..frames {
+        let time = loop_duration * frame as f64 / frames as f64;
+        let mut svg = Vec::new();
+        write_frame(&mut svg, scene, registry, time)?;
+
+        save_png(&svg, scene.options.width, scene.options.height, &dir.join(format!("frame_{:04}.png", frame)))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn canvas_options_keep_the_image_proportions() {
+        let options = canvas_options(400, 200);
+        assert_eq!((options.width, options.height), (2000, 1000));
+        let options = canvas_options(300, 600);
+        assert_eq!((options.width, options.height), (1000, 2000));
+        let options = canvas_options(1000, 1000);
+        assert_eq!((options.width, options.height), (1000, 1000));
+    }
+
+    #[test]
+    fn rosettas_show_over_the_background() {
+        let dir = std::env::temp_dir();
+        let curves = dir.join(format!("rustigraph_curves_{}.png", std::process::id()));
+        let background = dir.join(format!("rustigraph_background_{}.png", std::process::id()));
+        render_png(&crate::renderer::default_styles(), 120, 80, &curves).unwrap();
+        render_png(&[], 120, 80, &background).unwrap();
+        let load = |path: &Path| {
+            let pixmap = Pixmap::load_png(path).unwrap();
+            fs::remove_file(path).unwrap();
+            pixmap
+        };
+        let (curves, background) = (load(&curves), load(&background));
+        assert_eq!((curves.width(), curves.height()), (120, 80));
+
+        // The curves cover part of the canvas, and leave the background around them.
+        let changed = curves.pixels().iter().zip(background.pixels()).filter(|(p, q)| p != q).count();
+        assert!(changed > 120 * 80 / 100 && changed < 120 * 80, "{} pixels changed", changed);
+        assert!(matches!(render_png(&[], 0, 80, &dir.join("rustigraph_empty.png")), Err(RosettaError::Raster(_))));
+    }
+}
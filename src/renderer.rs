@@ -1,32 +1,600 @@
-use crate::rosetta::{Hypotrochoid, Coordinate};
-use std::fs::File;
+use crate::color::{ensure_contrast, mix};
+#[cfg(feature = "rand")]
+use crate::color::shift_hue;
+use crate::error::RosettaError;
+use crate::geometry::{polyline_length, quantize, BoundingBox};
+use crate::rosetta::{Hypotrochoid, Coordinate, Curve, CurveKind, CurveStats, format_path_data, format_smooth_path_data, gcd};
+use crate::scene::{validate_style, Scene, SceneCurve, StyleRef, StyleRegistry};
+use log::debug;
+#[cfg(feature = "rand")]
+use rand::{Rng, SeedableRng};
+#[cfg(feature = "rand")]
+use rand_pcg::Pcg64;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::{self, File};
 use std::io::{self, Write, BufWriter};
+use std::path::Path;
+use std::sync::Arc;
 
-/// Defines the visual style of a single rosetta curve.
-#[derive(Debug)]
-struct RosettaStyle {
-    pub outer_radius: f64,      // Radius of the outer, fixed circle.
-    pub inner_radius: f64,      // Radius of the inner, rolling circle.
-    pub distance: f64,          // Distance of the drawing pen from the center of the inner circle.
-    pub color: &'static str,    // Color of the rosetta curve.
-    pub duration: &'static str, // Duration of one full rotation of the rosetta curve.
+/// Scale factor applied to the whole composition around the canvas center.
+pub const CONTENT_SCALE: f64 = 1.4;
+
+/// Opacity of the curves not hovered in an interactive document.
+const HOVER_DIMMED_OPACITY: f64 = 0.25;
+
+/// Width of the curve strokes.
+const STROKE_WIDTH: f64 = 2.0;
+
+/// Colors given to the curves made up by `random_styles`.
+#[cfg(feature = "rand")]
+const RANDOM_PALETTE: [&str; 8] = ["cyan", "gold", "orange", "magenta", "lime", "pink", "#8ecae6", "#b388ff"];
+
+/// Largest number of petals of the curves made up by `random_styles`, beyond which they
+/// blur into a disc; the fewest is 3, below which they hardly look like flowers.
+#[cfg(feature = "rand")]
+const MAX_RANDOM_PETALS: u64 = 40;
+
+/// Number of arcs over which a gradient stroke fades from its start color to its end color.
+const GRADIENT_ARCS: usize = 32;
+
+/// Default length of the segments of curves without a pinned step count, in user units.
+pub const SEGMENT_LENGTH: f64 = 1.0;
+
+/// Font size of the curve captions.
+const LABEL_SIZE: f64 = 12.0;
+
+/// Defines a single rosetta curve: its shape, the pen drawing it and its animation.
+/// The parts are independent, so one pen can be reused across many geometries.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct RosettaStyle {
+    pub geometry: Geometry,   // Shape of the curve.
+    pub pen: Pen,             // Appearance of the stroke.
+    pub animation: Animation, // Motion of the curve.
+}
+
+/// Defines the parameters of the hypotrochoid traced by a rosetta.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Geometry {
+    pub outer_radius: f64,    // Radius of the outer, fixed circle.
+    pub inner_radius: f64,    // Radius of the inner, rolling circle.
+    pub distance: f64,        // Distance of the drawing pen from the center of the inner circle.
+    #[cfg_attr(feature = "serde", serde(with = "steps_count"))]
+    pub steps: Option<usize>, // Number of steps (points) used to approximate the curve, picked from its length when unset.
+    pub kind: CurveKind,      // Whether the circle rolls inside or outside the fixed one.
+}
+
+impl Default for Geometry {
+    fn default() -> Self {
+        Geometry {
+            outer_radius: 150.0,
+            inner_radius: 52.5,
+            distance: 97.5,
+            steps: Some(3000),
+            kind: CurveKind::Hypotrochoid,
+        }
+    }
+}
+
+impl Geometry {
+    /// Builds the curve described by this geometry, through the checks of `Hypotrochoid::new`.
+    /// Without a pinned step count, its segments are about `SEGMENT_LENGTH` long.
+    pub fn curve(&self) -> Result<Hypotrochoid, RosettaError> {
+        self.curve_with_segments(SEGMENT_LENGTH)
+    }
+
+    /// Builds the curve described by this geometry, picking the number of steps giving
+    /// segments about `target_segment_len` long unless it's pinned (see `Curve::adaptive_steps`).
+    pub fn curve_with_segments(&self, target_segment_len: f64) -> Result<Hypotrochoid, RosettaError> {
+        let curve = Hypotrochoid::new(self.outer_radius, self.inner_radius, self.distance, self.steps.unwrap_or(1), self.kind)?;
+        Ok(match self.steps {
+            Some(_) => curve,
+            None => {
+                let steps = curve.adaptive_steps(target_segment_len);
+                curve.with_steps(steps)
+            }
+        })
+    }
+}
+
+/// Reads and writes the step count of a geometry, `"auto"` standing for an unset count,
+/// since TOML has no `null` and a missing count takes the default one.
+#[cfg(feature = "serde")]
+mod steps_count {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    /// Holds a step count as found in a scene file.
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Steps {
+        Count(usize), // A pinned count.
+        Auto(String), // The `"auto"` keyword.
+    }
+
+    pub fn serialize<S: Serializer>(steps: &Option<usize>, serializer: S) -> Result<S::Ok, S::Error> {
+        match steps {
+            Some(steps) => serializer.serialize_u64(*steps as u64),
+            None => serializer.serialize_str("auto"),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<usize>, D::Error> {
+        match Steps::deserialize(deserializer)? {
+            Steps::Count(steps) => Ok(Some(steps)),
+            Steps::Auto(keyword) if keyword == "auto" => Ok(None),
+            Steps::Auto(keyword) => Err(D::Error::custom(format!("invalid steps '{}', expected a count or \"auto\"", keyword))),
+        }
+    }
+}
+
+/// Defines the stroke drawing a rosetta curve.
+/// The color may be a CSS variable, e.g. `var(--curve-color, cyan)`, to theme the
+/// curves from the stylesheet of the embedding page (see `color::is_css_color`).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Pen {
+    pub color: String,                      // Color of the rosetta curve.
+    pub width: f64,                         // Width of the stroke.
+    pub opacity: f64,                       // Opacity of the stroke.
+    pub dash: Option<String>,               // SVG dash pattern (e.g. "4 2"), ignored when drawing and erasing.
+    pub segments: Option<Vec<String>>,      // Colors of equal arcs splitting the curve, replacing `color`.
+    pub gradient: Option<(String, String)>, // Start and end colors faded along the curve, replacing `color`.
+    pub smooth: bool,                       // Joins the points with Bézier curves instead of straight lines.
+}
+
+impl Default for Pen {
+    fn default() -> Self {
+        Pen {
+            color: String::from("cyan"),
+            width: STROKE_WIDTH,
+            opacity: 1.0,
+            dash: None,
+            segments: None,
+            gradient: None,
+            smooth: false,
+        }
+    }
+}
+
+impl Pen {
+    /// Returns whether the curve is stroked as several arcs of their own colors,
+    /// which keeps it from sharing a symbol with identical curves.
+    pub fn is_segmented(&self) -> bool {
+        self.segments.is_some() || self.gradient.is_some()
+    }
+
+    /// Returns the colors of the arcs stroking the curve, if it's split into arcs.
+    /// Explicit segments take precedence over a gradient, which is spread over `GRADIENT_ARCS` arcs.
+    fn arc_colors(&self) -> Option<Cow<'_, [String]>> {
+        if let Some(colors) = &self.segments {
+            return Some(Cow::Borrowed(colors));
+        }
+        self.gradient.as_ref().map(|(start, end)| {
+            (0..GRADIENT_ARCS).map(|k| mix(start, end, k as f64 / (GRADIENT_ARCS - 1) as f64)).collect()
+        })
+    }
+}
+
+/// Defines how a rosetta curve moves once the document is loaded.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Animation {
+    pub duration: String,              // Duration of one full rotation of the rosetta curve.
+    pub rotate_from_deg: f64,          // Starting angle of the rotation animation, in degrees.
+    pub rotate_to_deg: f64,            // Ending angle of the rotation animation, in degrees.
+    pub alternate: bool,               // Rocks back and forth between both angles instead of looping.
+    pub delay: Option<String>,         // CSS time value (e.g. "1.5s") delaying the start of the rotation.
+    pub phase: f64,                    // How far into its rotation the curve starts, in degrees of a whole cycle (0 to 360).
+    pub draw_erase: bool,              // Loops drawing then erasing the curve; replaces any dash pattern.
+    pub chain: bool,                   // Draws the curve on once, after the previous chained curve; overrides `draw_erase`.
+    pub draw_in: bool,                 // Draws the curve on once at load, after its delay; overrides `draw_erase`.
+    pub twinkle: Option<TwinkleStyle>, // Makes the stroke opacity oscillate.
+}
+
+impl Default for Animation {
+    fn default() -> Self {
+        Animation {
+            duration: String::from("6s"),
+            rotate_from_deg: 0.0,
+            rotate_to_deg: 360.0,
+            alternate: false,
+            delay: None,
+            phase: 0.0,
+            draw_erase: false,
+            chain: false,
+            draw_in: false,
+            twinkle: None,
+        }
+    }
+}
+
+/// Defines a twinkle effect, where the stroke opacity fades down and back up.
+/// Giving each curve its own `begin` offset makes them twinkle out of sync.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct TwinkleStyle {
+    pub min_opacity: f64, // Lowest opacity reached by the stroke.
+    pub duration: String, // CSS time value of one whole fade cycle.
+    pub begin: String,    // CSS time value offsetting the cycle, possibly negative.
+}
+
+impl Default for TwinkleStyle {
+    fn default() -> Self {
+        TwinkleStyle {
+            min_opacity: 0.4,
+            duration: String::from("3s"),
+            begin: String::from("0s"),
+        }
+    }
+}
+
+/// Defines a text written along the path of a curve.
+/// Very wiggly curves bend and overlap the glyphs, making the text hard to read:
+/// curves with wide, smooth loops suit it best.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct TextOnPath {
+    pub text: String,         // Text to write, following the curve from its first point.
+    pub font_family: String,  // CSS font family of the text.
+    pub font_size: f64,       // Font size of the text, in user units.
+    pub color: String,        // Fill color of the glyphs.
+    pub start_offset: String, // Distance along the path where the text starts, e.g. "25%".
+}
+
+impl Default for TextOnPath {
+    fn default() -> Self {
+        TextOnPath {
+            text: String::new(),
+            font_family: String::from("sans-serif"),
+            font_size: LABEL_SIZE,
+            color: String::from("white"),
+            start_offset: String::from("0%"),
+        }
+    }
+}
+
+/// Defines small random variations of the color and opacity of every curve,
+/// making a set of curves look hand-drawn. The variations come from a generator
+/// seeded with `seed`, drawn curve after curve in scene order: the same seed and
+/// scene always give the same document, from one run or platform to the next.
+#[cfg(feature = "rand")]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct JitterStyle {
+    pub seed: u64,          // Seed of the random generator.
+    pub hue_range: f64,     // Largest hue shift either way, in degrees.
+    pub opacity_range: f64, // Largest change of the stroke opacity either way.
+}
+
+#[cfg(feature = "rand")]
+impl Default for JitterStyle {
+    fn default() -> Self {
+        JitterStyle {
+            seed: 0,
+            hue_range: 10.0,
+            opacity_range: 0.1,
+        }
+    }
+}
+
+impl RosettaStyle {
+    /// Builds the curve described by this style, rejecting degenerate geometries.
+    pub fn curve(&self) -> Result<Hypotrochoid, RosettaError> {
+        self.geometry.curve()
+    }
+}
+
+/// Defines a rosetta style with all its fields side by side, as styles used to be.
+/// Converts into a `RosettaStyle`, the pen keeping its default width and opacity.
+#[derive(Debug, Clone)]
+pub struct FlatStyle {
+    pub outer_radius: f64,             // Radius of the outer, fixed circle.
+    pub inner_radius: f64,             // Radius of the inner, rolling circle.
+    pub distance: f64,                 // Distance of the drawing pen from the center of the inner circle.
+    pub steps: usize,                  // Number of steps (points) used to approximate the curve.
+    pub color: String,                 // Color of the rosetta curve.
+    pub duration: String,              // Duration of one full rotation of the rosetta curve.
+    pub rotate_from_deg: f64,          // Starting angle of the rotation animation, in degrees.
+    pub rotate_to_deg: f64,            // Ending angle of the rotation animation, in degrees.
+    pub alternate: bool,               // Rocks back and forth between both angles instead of looping.
+    pub delay: Option<String>,         // CSS time value (e.g. "1.5s") delaying the start of the rotation.
+    pub draw_erase: bool,              // Loops drawing then erasing the curve.
+    pub twinkle: Option<TwinkleStyle>, // Makes the stroke opacity oscillate.
+}
+
+impl From<FlatStyle> for RosettaStyle {
+    fn from(flat: FlatStyle) -> Self {
+        RosettaStyle {
+            geometry: Geometry {
+                outer_radius: flat.outer_radius,
+                inner_radius: flat.inner_radius,
+                distance: flat.distance,
+                steps: Some(flat.steps),
+                kind: CurveKind::Hypotrochoid,
+            },
+            pen: Pen {
+                color: flat.color,
+                ..Default::default()
+            },
+            animation: Animation {
+                duration: flat.duration,
+                rotate_from_deg: flat.rotate_from_deg,
+                rotate_to_deg: flat.rotate_to_deg,
+                alternate: flat.alternate,
+                delay: flat.delay,
+                draw_erase: flat.draw_erase,
+                twinkle: flat.twinkle,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// Defines the document-level options of the SVG output.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct RenderOptions {
+    pub width: u32,                           // Width of the viewBox, in user units.
+    pub height: u32,                          // Height of the viewBox, in user units.
+    pub preserve_aspect_ratio: String,        // How the viewBox is fitted into the viewport.
+    pub background_color: String,             // Color filling the canvas, under the background pattern.
+    pub background: Background,               // Pattern drawn behind the rosettas.
+    pub minify: bool,                         // Strips indentation and newlines to shrink the output.
+    pub min_contrast: Option<f64>,            // Minimum contrast ratio of the curves against the background color.
+    pub units: Units,                         // Unit of the document size, for plotters and laser cutters.
+    pub transform: Option<String>,            // SVG transform applied to the grid and curves together.
+    pub quantize: Option<f64>,                // Grid step the path coordinates are snapped to.
+    pub standalone: bool,                     // Starts with the XML prolog; turn off to inline the SVG into HTML.
+    pub margin: Option<f64>,                  // Fits the canvas around the curves, leaving this margin on every side.
+    pub mode: RenderMode,                     // Whether the curves move, or stand still.
+    pub animation_backend: AnimationBackend,  // Markup the curves are animated with.
+    pub interactive: bool,                    // Dims the other curves while one of them is hovered.
+    pub segment_length: f64,                  // Length of the segments of curves without a pinned step count, in user units.
+    #[cfg(feature = "rand")]
+    pub color_jitter: Option<JitterStyle>,    // Random variations of the curve colors and opacities.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub transform_fn: Option<PointTransform>, // Warps every point of the curves before they're placed.
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        RenderOptions {
+            width: 1000,
+            height: 1000,
+            preserve_aspect_ratio: String::from("xMidYMid meet"),
+            background_color: String::from("#222"),
+            background: Background::default(),
+            minify: false,
+            min_contrast: None,
+            units: Units::default(),
+            transform: None,
+            quantize: None,
+            standalone: true,
+            margin: None,
+            mode: RenderMode::default(),
+            animation_backend: AnimationBackend::default(),
+            interactive: false,
+            segment_length: SEGMENT_LENGTH,
+            #[cfg(feature = "rand")]
+            color_jitter: None,
+            transform_fn: None,
+        }
+    }
+}
+
+/// Warps the points of every curve, e.g. with a sine distortion or a perspective,
+/// applied right after `compute_points` and before the curve is scaled and placed.
+///
+/// The curve is recentered before the warp, not after, and its closure is only
+/// preserved by continuous transforms: one that isn't bijective may fold the curve
+/// onto itself, tear it apart, or move it away from the center of its rotation.
+#[derive(Clone)]
+pub struct PointTransform(pub Arc<dyn Fn(Coordinate) -> Coordinate + Send + Sync>);
+
+impl PointTransform {
+    /// Wraps a function mapping each point to its warped position.
+    pub fn new(transform: impl Fn(Coordinate) -> Coordinate + Send + Sync + 'static) -> Self {
+        PointTransform(Arc::new(transform))
+    }
+}
+
+impl fmt::Debug for PointTransform {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("PointTransform(..)")
+    }
+}
+
+/// Transforms are code and can't be compared, so they're only equal to their own clones.
+impl PartialEq for PointTransform {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// Selects the unit in which the document size is given.
+///
+/// Coordinates always stay in user units inside the `viewBox`. The root `width` and
+/// `height` are set to the canvas size in that unit, so that one user unit spans
+/// exactly one pixel, millimeter or inch: a 200 x 150 canvas in millimeters prints
+/// at 200mm x 150mm, with every point at its coordinate in mm. Such documents look
+/// the same whether opened on their own or embedded, unlike viewport-filling ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Units {
+    #[default]
+    Pixels,      // One user unit per pixel.
+    Millimeters, // One user unit per millimeter.
+    Inches,      // One user unit per inch.
+    Viewport,    // Fills the viewport, whatever its size.
+}
+
+impl Units {
+    /// Returns the SVG unit suffix of a fixed-size unit, or `None` when filling the viewport.
+    fn suffix(self) -> Option<&'static str> {
+        match self {
+            Units::Pixels => Some(""),
+            Units::Millimeters => Some("mm"),
+            Units::Inches => Some("in"),
+            Units::Viewport => None,
+        }
+    }
+}
+
+/// Selects whether the document moves at all.
+/// A static document is a still frame at load time, without any animation, style
+/// keyframes or black overlay, so rasterizers and print converters always get the
+/// same image. The curves sit at their starting angle, fully drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RenderMode {
+    #[default]
+    Animated, // Curves rotate, twinkle and draw themselves as their animations say.
+    Static,   // Curves stand still, as in the frame written at time 0.
+}
+
+/// Selects how the curves are animated.
+/// Both backends give the same motion, but some renderers and sanitizers strip SMIL
+/// elements while honoring CSS animations. With CSS, each curve is followed by a
+/// `<style>` element holding its `@keyframes` and the rules applying them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AnimationBackend {
+    #[default]
+    Smil, // `<animate>` and `<animateTransform>` elements inside the curves.
+    Css,  // `@keyframes` and `animation` properties, one set per curve.
+}
+
+/// Defines the background drawn behind the rosettas.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Background {
+    None,                      // Nothing over the background color.
+    Solid(String),             // A single flat color.
+    LineGrid(GridStyle),       // A grid of thin lines.
+    DotGrid(DotStyle),         // A grid of small dots.
+    Checker(CheckerStyle),     // A checkerboard of alternating squares.
+}
+
+impl Default for Background {
+    fn default() -> Self {
+        Background::LineGrid(GridStyle::default())
+    }
+}
+
+/// Defines the visual style of the background grid, the default background.
+/// The step needn't be whole, to align the grid to any spacing.
+/// `Background::None` draws no grid, leaving the background color bare.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct GridStyle {
+    pub step: f64,         // Spacing between grid lines.
+    pub color: String,     // Color of the grid lines.
+    pub stroke_width: f32, // Width of the grid lines.
+    pub opacity: f32,      // Opacity of the grid lines.
+}
+
+impl Default for GridStyle {
+    fn default() -> Self {
+        GridStyle {
+            step: 50.0,
+            color: String::from("white"),
+            stroke_width: 0.5,
+            opacity: 0.2,
+        }
+    }
 }
-/// Defines the visual style of the background grid.
-#[derive(Debug)]
-struct GridStyle {
-    pub step: u32,           // Spacing between grid lines.
-    pub color: &'static str, // Color of the grid lines.
-    pub stroke_width: f32,   // Width of the grid lines.
-    pub opacity: f32,        // Opacity of the grid lines.
+
+/// Defines the visual style of the background dots.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct DotStyle {
+    pub step: u32,     // Spacing between dots.
+    pub radius: f32,   // Radius of a single dot.
+    pub color: String, // Color of the dots.
+    pub opacity: f32,  // Opacity of the dots.
+}
+
+impl Default for DotStyle {
+    fn default() -> Self {
+        DotStyle {
+            step: 25,
+            radius: 1.0,
+            color: String::from("white"),
+            opacity: 0.3,
+        }
+    }
+}
+
+/// Defines the visual style of the background checkerboard.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct CheckerStyle {
+    pub size: u32,     // Side of a single square.
+    pub color: String, // Color of the filled squares.
+    pub opacity: f32,  // Opacity of the filled squares.
+}
+
+impl Default for CheckerStyle {
+    fn default() -> Self {
+        CheckerStyle {
+            size: 50,
+            color: String::from("white"),
+            opacity: 0.05,
+        }
+    }
+}
+
+/// Lists the attributes of the root `<svg>` element, besides its namespace.
+/// Use `"xMidYMid slice"` as aspect ratio for a full-bleed background.
+fn root_attributes(options: &RenderOptions) -> Vec<(&'static str, String)> {
+    let (width, height) = match options.units.suffix() {
+        Some(unit) => (format!("{}{}", options.width, unit), format!("{}{}", options.height, unit)),
+        None => (String::from("100%"), String::from("100%")),
+    };
+    vec![
+        ("version", String::from("1.1")),
+        ("width", width),
+        ("height", height),
+        ("viewBox", format!("0 0 {} {}", options.width, options.height)),
+        ("preserveAspectRatio", options.preserve_aspect_ratio.clone()),
+    ]
+}
+
+/// Writes the XML prolog of standalone documents and opens the root `<svg>` element.
+/// Without the prolog, the document is an `<svg>` fragment that can be inlined into HTML.
+fn write_root_open(writer: &mut impl Write, options: &RenderOptions) -> io::Result<()> {
+    if options.standalone {
+        writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    }
+    write!(writer, r#"    <svg xmlns="http://www.w3.org/2000/svg""#)?;
+    for (name, value) in root_attributes(options) {
+        write!(writer, r#" {}="{}""#, name, value)?;
+    }
+    writeln!(writer, ">")?;
+    Ok(())
 }
 
 /// Writes the SVG header, including styles and filters.
-fn write_header(writer: &mut impl Write) -> io::Result<()> {
-    let svg_begin = 
-    r##"<?xml version="1.0" encoding="UTF-8"?>
-    <svg xmlns="http://www.w3.org/2000/svg" version="1.1" width="100%" height="100%">
-    <rect width="100%" height="100%" fill="#222" />
-    <defs>
+/// A still document keeps the glow, but neither the rainbow cycle nor the fade from black.
+fn write_header(writer: &mut impl Write, options: &RenderOptions, animated: bool) -> io::Result<()> {
+    writeln!(writer, r#"    <rect width="{}" height="{}" fill="{}" />"#, options.width, options.height, options.background_color)?;
+    let glow = 
+    r##"    <defs>
         <filter id="glow">
         <feGaussianBlur stdDeviation="1.5" result="coloredBlur"/>
         <feMerge>
@@ -36,21 +604,23 @@ fn write_header(writer: &mut impl Write) -> io::Result<()> {
         </filter>
     </defs>
     <style>
-        @keyframes rainbow-cycle {
+"##;
+    let rainbow = r##"        @keyframes rainbow-cycle {
         0% { filter: hue-rotate(0deg); }
         100% { filter: hue-rotate(360deg); }
         }
 
         #rosettas {
-        transform: translate(50%, 50%) scale(1.4);
         animation: rainbow-cycle 5s linear infinite; 
         }
         
-        path {
+"##;
+    let glow_rule = r##"        path {
         filter: url(#glow);
         }
         
-        @keyframes fadeFromBlack {
+"##;
+    let fade = r##"        @keyframes fadeFromBlack {
 			from {
 				opacity: 1;
 			}
@@ -63,107 +633,1506 @@ fn write_header(writer: &mut impl Write) -> io::Result<()> {
 			animation: fadeFromBlack 5s ease-in forwards;	
 			pointer-events: none;
 		}
-    </style>
-    "##;
-    writer.write_all(svg_begin.as_bytes())?;
+"##;
+    writer.write_all(glow.as_bytes())?;
+    if animated {
+        writer.write_all(rainbow.as_bytes())?;
+    }
+    writer.write_all(glow_rule.as_bytes())?;
+    if animated {
+        writer.write_all(fade.as_bytes())?;
+    }
+    writer.write_all(b"    </style>\n    ")?;
+    Ok(())
+}
+
+/// Writes the rules dimming every curve but the hovered one, found through their `curve` class.
+/// A curve only counts as hovered while the pointer is over its stroke, not over the gaps between its lines.
+fn write_hover_style(writer: &mut impl Write) -> io::Result<()> {
+    writeln!(writer, "    <style>")?;
+    writeln!(writer, "        .curve {{ transition: opacity 0.3s; }}")?;
+    writeln!(writer, "        svg:has(.curve:hover) .curve:not(:hover) {{ opacity: {}; }}", HOVER_DIMMED_OPACITY)?;
+    writeln!(writer, "    </style>")?;
     Ok(())
 }
 
-/// Writes the closing tags for the SVG file.
-fn write_footer(writer: &mut impl Write) -> io::Result<()> {
+/// Writes the overlay covering the whole SVG file, fading out on load.
+fn write_footer(writer: &mut impl Write, options: &RenderOptions) -> io::Result<()> {
+    writeln!(writer, r#"<rect id="black-overlay" width="{}" height="{}" fill="black" />"#, options.width, options.height)?;
+    Ok(())
+}
+
+/// Writes the closing tag of the root `<svg>` element.
+fn write_root_close(writer: &mut impl Write) -> io::Result<()> {
     let svg_end = 
-    r#"<rect id="black-overlay" width="100%" height="100%" fill="black" />
-    </svg>
+    r#"    </svg>
     "#;
     writer.write_all(svg_end.as_bytes())?;
     Ok(())
 }
 
+/// Writes the background to the SVG file.
+fn write_background(writer: &mut impl Write, options: &RenderOptions) -> io::Result<()> {
+    match &options.background {
+        Background::None => Ok(()),
+        Background::Solid(color) => {
+            writeln!(writer, r#" <rect width="{}" height="{}" fill="{}" />"#, options.width, options.height, color)
+        }
+        Background::LineGrid(style) => write_grid(writer, options, style),
+        Background::DotGrid(style) => write_dots(writer, options, style),
+        Background::Checker(style) => write_checker(writer, options, style),
+    }
+}
+
 /// Writes a grid pattern to the SVG file.
-fn write_grid(writer: &mut impl Write) -> io::Result<()> {
-     let style = GridStyle {
-        step: 50,
-        color: "white",
-        stroke_width: 0.5,
-        opacity: 0.2,
-    };
+fn write_grid(writer: &mut impl Write, options: &RenderOptions, style: &GridStyle) -> io::Result<()> {
     let pattern_id = "grid_pattern";
     writeln!(writer, " <defs>")?;
     writeln!(writer, r#"  <pattern id="{}" width="{}" height="{}" patternUnits="userSpaceOnUse">"#, pattern_id, style.step, style.step)?;
     writeln!(writer, r#"   <path d="M {} 0 L 0 0 0 {}" fill="none" stroke="{}" stroke-width="{}" opacity="{}" />"#, style.step, style.step, style.color, style.stroke_width, style.opacity)?;
     writeln!(writer, "  </pattern>")?;
     writeln!(writer, " </defs>")?;
-    writeln!(writer, r#" <rect width="100%" height="100%" fill="url(#{})" />"#, pattern_id)?;
+    writeln!(writer, r#" <rect width="{}" height="{}" fill="url(#{})" />"#, options.width, options.height, pattern_id)?;
     Ok(())
 }
 
-/// Creates the final SVG file with multiple rosetta patterns.
-pub fn create_svg_rosettas() -> io::Result<()>{
-    let file = File::create("rosettas.svg")?;
-    let mut writer = BufWriter::new(file);
+/// Writes a dotted pattern to the SVG file, with one dot centered in each cell.
+fn write_dots(writer: &mut impl Write, options: &RenderOptions, style: &DotStyle) -> io::Result<()> {
+    let pattern_id = "dot_pattern";
+    let center = style.step as f32 / 2.0;
+    writeln!(writer, " <defs>")?;
+    writeln!(writer, r#"  <pattern id="{}" width="{}" height="{}" patternUnits="userSpaceOnUse">"#, pattern_id, style.step, style.step)?;
+    writeln!(writer, r#"   <circle cx="{}" cy="{}" r="{}" fill="{}" opacity="{}" />"#, center, center, style.radius, style.color, style.opacity)?;
+    writeln!(writer, "  </pattern>")?;
+    writeln!(writer, " </defs>")?;
+    writeln!(writer, r#" <rect width="{}" height="{}" fill="url(#{})" />"#, options.width, options.height, pattern_id)?;
+    Ok(())
+}
 
-    write_header(&mut writer)?;
-    write_grid(&mut writer)?;
+/// Writes a checkerboard pattern to the SVG file.
+/// Each pattern tile holds two filled squares on its diagonal.
+fn write_checker(writer: &mut impl Write, options: &RenderOptions, style: &CheckerStyle) -> io::Result<()> {
+    let pattern_id = "checker_pattern";
+    let tile = style.size * 2;
+    writeln!(writer, " <defs>")?;
+    writeln!(writer, r#"  <pattern id="{}" width="{}" height="{}" patternUnits="userSpaceOnUse">"#, pattern_id, tile, tile)?;
+    writeln!(writer, r#"   <rect width="{}" height="{}" fill="{}" opacity="{}" />"#, style.size, style.size, style.color, style.opacity)?;
+    writeln!(writer, r#"   <rect x="{}" y="{}" width="{}" height="{}" fill="{}" opacity="{}" />"#, style.size, style.size, style.size, style.size, style.color, style.opacity)?;
+    writeln!(writer, "  </pattern>")?;
+    writeln!(writer, " </defs>")?;
+    writeln!(writer, r#" <rect width="{}" height="{}" fill="url(#{})" />"#, options.width, options.height, pattern_id)?;
+    Ok(())
+}
 
-    let styles = [
+/// Returns the predefined set of rosettas.
+pub fn default_styles() -> Vec<RosettaStyle> {
+    vec![
+        RosettaStyle::default(),
         RosettaStyle {
-            outer_radius: 150.0,
-            inner_radius: 52.5,
-            distance: 97.5,
-            color: "cyan",
-            duration: "6s",
+            geometry: Geometry { outer_radius: 160.0, inner_radius: 110.0, distance: 85.0, ..Default::default() },
+            pen: Pen { color: String::from("gold"), ..Default::default() },
+            animation: Animation { duration: String::from("14s"), ..Default::default() },
         },
         RosettaStyle {
-            outer_radius: 160.0,
-            inner_radius: 110.0,
-            distance: 85.0,
-            color: "gold",
-            duration: "14s",
+            geometry: Geometry { outer_radius: 120.0, inner_radius: 33.0, distance: 66.0, ..Default::default() },
+            pen: Pen { color: String::from("orange"), ..Default::default() },
+            animation: Animation { duration: String::from("4s"), ..Default::default() },
         },
-        RosettaStyle {
-            outer_radius: 120.0,
-            inner_radius: 33.0,
-            distance: 66.0,
-            color: "orange",
-            duration: "4s",
-        },
-    ];
-    for style in &styles {
-        write_rosetta(&mut writer, style)?;
+    ]
+}
+
+/// Makes up `count` rosettas with random radii, colors and durations.
+/// The same seed always gives the same rosettas, to share or compare a set.
+///
+/// Radii are whole numbers, the rolling circle smaller than the fixed one, so every curve
+/// closes; combinations with too few or too many petals (`R / gcd(R, r)`) are drawn again.
+/// The pen offset lies between a third and the whole of the rolling radius, so no curve
+/// spills out of the fixed circle, and each curve gets enough steps to stay smooth over all
+/// its revolutions.
+#[cfg(feature = "rand")]
+pub fn random_styles(count: usize, seed: u64) -> Vec<RosettaStyle> {
+    let mut rng = Pcg64::seed_from_u64(seed);
+    let mut styles = Vec::with_capacity(count);
+    while styles.len() < count {
+        let outer: u64 = rng.random_range(100..=200);
+        let inner: u64 = rng.random_range(10..outer);
+        let divisor = gcd(outer, inner);
+        if !(3..=MAX_RANDOM_PETALS).contains(&(outer / divisor)) {
+            continue;
+        }
+        let distance = (rng.random_range(inner as f64 / 3.0..=inner as f64) * 10.0).round() / 10.0;
+        let revolutions = (inner / divisor) as usize;
+        styles.push(RosettaStyle {
+            geometry: Geometry {
+                outer_radius: outer as f64,
+                inner_radius: inner as f64,
+                distance,
+                steps: Geometry::default().steps.map(|steps| steps.max(revolutions * 200)),
+                ..Default::default()
+            },
+            pen: Pen { color: String::from(RANDOM_PALETTE[rng.random_range(0..RANDOM_PALETTE.len())]), ..Default::default() },
+            animation: Animation { duration: format!("{}s", rng.random_range(4..=16)), ..Default::default() },
+        });
     }
-   
-    write_footer(&mut writer)?;
+    debug!("made up {} random rosettas from seed {}", count, seed);
+    styles
+}
+
+/// Computes the statistics of each predefined rosetta.
+pub fn default_stats() -> Vec<CurveStats> {
+    default_styles().iter().map(|style| style.curve().expect("the predefined rosettas are valid").stats()).collect()
+}
+
+/// Creates the final SVG file with multiple rosetta patterns.
+pub fn create_svg_rosettas(options: &RenderOptions) -> Result<(), RosettaError> {
+    let styles = default_styles();
+    fs::write("rosettas.svg", render_svg(&styles, options)?)?;
+    debug!("wrote {} rosettas to rosettas.svg", styles.len());
     Ok(())
 }
 
-/// Writes a single rosetta curve to the SVG file.
-fn write_rosetta(writer: &mut impl Write, style: &RosettaStyle) -> io::Result<()> {
-    writeln!(writer, r#"  <g id="rosettas">"#)?;
-    writeln!(writer, r#"    <g transform="rotate(0)">"#)?;
-    write!(writer, r#"      <path fill="none" stroke-width="2" stroke="{}" d="#, style.color)?;
-    let curve = Hypotrochoid {
-    outer_radius: style.outer_radius,
-    inner_radius: style.inner_radius,
-    pen_offset: style.distance,
-    steps: 3000,
+/// Renders rosettas, all centered on the canvas, into a complete SVG document held in memory:
+/// header, background, every curve in order, and footer. Suits embedding the animation
+/// into a larger tool; build a `Scene` instead to place, scale or label the curves.
+pub fn render_svg(styles: &[RosettaStyle], options: &RenderOptions) -> Result<String, RosettaError> {
+    let mut content = Vec::new();
+    write_scene(&mut content, &centered_scene(styles, options), &StyleRegistry::default())?;
+    Ok(String::from_utf8(content).expect("the document is written from UTF-8 text only"))
+}
+
+/// Builds a scene drawing every style at the center of the canvas.
+pub(crate) fn centered_scene(styles: &[RosettaStyle], options: &RenderOptions) -> Scene {
+    Scene {
+        options: options.clone(),
+        curves: styles.iter().cloned().map(SceneCurve::from).collect(),
+    }
+}
+
+/// Identifies the path of a curve: its geometry parameters, scale, stroke width and smoothing.
+/// Floats are taken as raw bits.
+type CurveKey = (CurveKind, u64, u64, u64, Option<usize>, u64, u64, bool);
+
+/// Computes the key of a curve, equal for curves producing identical paths.
+fn curve_key(curve: &SceneCurve, style: &RosettaStyle) -> CurveKey {
+    let geometry = &style.geometry;
+    (
+        geometry.kind,
+        geometry.outer_radius.to_bits(),
+        geometry.inner_radius.to_bits(),
+        geometry.distance.to_bits(),
+        geometry.steps,
+        curve.scale.to_bits(),
+        style.pen.width.to_bits(),
+        style.pen.smooth,
+    )
+}
+
+/// Writes a whole scene as an SVG document.
+/// Named styles are looked up in the registry as the curves are written.
+///
+/// Curves sharing the same points are written once as a `<symbol>`, then drawn
+/// through `<use>` instances carrying their own color, position and animation.
+/// A scene without curves makes a blank template: only the background, the grid
+/// and the overlay are written.
+/// The scene and the named styles it uses are validated first, so values such as
+/// colors and durations can't break out of the attributes they're written into.
+pub fn write_scene(writer: &mut impl Write, scene: &Scene, registry: &StyleRegistry) -> Result<(), RosettaError> {
+    let scene = fitted_scene(scene, registry)?;
+    if scene.options.minify {
+        let mut minifier = Minifier { inner: writer, line_start: true };
+        write_document(&mut minifier, &scene, registry)
+    } else {
+        write_document(writer, &scene, registry)
+    }
+}
+
+/// Writes the SVG document of a scene, with its original layout.
+fn write_document(writer: &mut impl Write, scene: &Scene, registry: &StyleRegistry) -> Result<(), RosettaError> {
+    write_root_open(writer, &scene.options)?;
+    write_content(writer, scene, registry, None)?;
+    write_root_close(writer)?;
+    Ok(())
+}
+
+/// Writes a still frame of the scene, as it would appear `time` seconds after loading.
+/// Each curve is frozen at its rotation angle at that time, and no animation is emitted.
+pub fn write_frame(writer: &mut impl Write, scene: &Scene, registry: &StyleRegistry, time: f64) -> Result<(), RosettaError> {
+    let scene = fitted_scene(scene, registry)?;
+    write_root_open(writer, &scene.options)?;
+    write_content(writer, &scene, registry, Some(time))?;
+    write_root_close(writer)?;
+    Ok(())
+}
+
+/// Writes each curve of the scene as its own standalone SVG file, `curve_0.svg` onwards,
+/// plus an `index.svg` laying them all out as a grid of `<image>` thumbnails.
+/// Every curve file keeps the scene options, so it can also be viewed on its own,
+/// and drops the curve offset, so the curve sits at the center of its own canvas.
+pub fn render_gallery(scene: &Scene, registry: &StyleRegistry, dir: &Path) -> Result<(), RosettaError> {
+    fs::create_dir_all(dir)?;
+    for (i, curve) in scene.curves.iter().enumerate() {
+        let single = Scene {
+            options: scene.options.clone(),
+            curves: vec![SceneCurve { offset: Coordinate { x: 0.0, y: 0.0 }, ..curve.clone() }],
+        };
+        let mut writer = BufWriter::new(File::create(dir.join(format!("curve_{}.svg", i)))?);
+        write_scene(&mut writer, &single, registry)?;
+        writer.flush()?;
+    }
+
+    let mut writer = BufWriter::new(File::create(dir.join("index.svg"))?);
+    write_gallery_index(&mut writer, &scene.options, scene.curves.len())?;
+    writer.flush()?;
+    debug!("wrote a gallery of {} curves to {}", scene.curves.len(), dir.display());
+    Ok(())
+}
+
+/// Writes the index of a gallery, referencing `count` curve files laid out row by row.
+fn write_gallery_index(writer: &mut impl Write, options: &RenderOptions, count: usize) -> io::Result<()> {
+    let columns = (count as f64).sqrt().ceil().max(1.0) as usize;
+    let rows = count.div_ceil(columns).max(1);
+    let cell_width = options.width as f64 / columns as f64;
+    let cell_height = options.height as f64 / rows as f64;
+
+    write_root_open(writer, options)?;
+    writeln!(writer, r#"    <rect width="{}" height="{}" fill="{}" />"#, options.width, options.height, options.background_color)?;
+    for i in 0..count {
+        let x = (i % columns) as f64 * cell_width;
+        let y = (i / columns) as f64 * cell_height;
+        writeln!(writer, r#"    <image id="curve_{0}" href="curve_{0}.svg" x="{1}" y="{2}" width="{3}" height="{4}" />"#, i, x, y, cell_width, cell_height)?;
+    }
+    write_root_close(writer)
+}
+
+/// Writes an SVG document whose canvas is filled with copies of a single curve,
+/// repeated through a `<pattern>` tile sized to the curve's bounding box.
+///
+/// Closed curves tile seamlessly, their copies touching at their extremities.
+/// An open curve leaves its loose ends visible in every tile. Curves whose bounding box
+/// isn't finite can't be tiled, and are rejected. So are segmented and gradient pens,
+/// since the tile holds a single path of a single color.
+pub fn write_tiled(writer: &mut impl Write, style: &RosettaStyle, options: &RenderOptions) -> Result<(), RosettaError> {
+    let pattern_id = "tile_pattern";
+    Scene { options: options.clone(), curves: vec![SceneCurve::from(style.clone())] }.validate()?;
+    if style.pen.is_segmented() {
+        return Err(RosettaError::InvalidStyle(String::from("can't tile a curve stroked in segments or a gradient")));
+    }
+    let curve = style.geometry.curve_with_segments(options.segment_length)?;
+    let bounds = curve.bounds();
+
+    // The tile leaves room for the stroke, so it isn't clipped on the tile edges.
+    let (width, height) = (bounds.width() + style.pen.width, bounds.height() + style.pen.width);
+    if !width.is_finite() || !height.is_finite() {
+        return Err(RosettaError::InvalidCurve(format!("can't tile a curve spanning {} x {}", bounds.width(), bounds.height())));
+    }
+
+    write_root_open(writer, options)?;
+    let animated = options.mode == RenderMode::Animated;
+    write_header(writer, options, animated)?;
+    write_background(writer, options)?;
+    writeln!(writer, " <defs>")?;
+    writeln!(writer, r#"  <pattern id="{}" width="{}" height="{}" patternUnits="userSpaceOnUse">"#, pattern_id, width, height)?;
+    writeln!(writer, r#"   <g transform="translate({} {})">"#, width / 2.0, height / 2.0)?;
+    write!(writer, r#"    <path fill="none" stroke-width="{}" stroke="{}" d="#, style.pen.width, style.pen.color)?;
+    let mut points = curve.compute_points();
+    if let Some(transform) = &options.transform_fn {
+        points.iter_mut().for_each(|p| *p = (transform.0)(*p));
+    }
+    if let Some(step) = options.quantize {
+        points = quantize(&points, step);
+    }
+    write_pen_path(writer, &points, &style.pen)?;
+    write!(writer, "{}", stroke_opacity(&style.pen))?;
+    write_pen_attributes(writer, &style.pen)?;
+    writeln!(writer, r#"></path>"#)?;
+    writeln!(writer, "   </g>")?;
+    writeln!(writer, "  </pattern>")?;
+    writeln!(writer, " </defs>")?;
+    writeln!(writer, r#" <rect width="{}" height="{}" fill="url(#{})" />"#, options.width, options.height, pattern_id)?;
+    if animated {
+        write_footer(writer, options)?;
+    }
+    write_root_close(writer)?;
+    Ok(())
+}
+
+/// Computes the box enclosing every curve of the scene wherever its rotation takes it,
+/// relative to the canvas center and before the content scale. A rotating curve sweeps
+/// the disc reaching its farthest point, while a still one keeps its angle.
+/// Captions aren't taken into account.
+pub fn combined_bounds(scene: &Scene, registry: &StyleRegistry) -> Result<BoundingBox, RosettaError> {
+    let mut corners = Vec::with_capacity(2 * scene.curves.len());
+    for curve in &scene.curves {
+        let style = curve.style.resolve(registry)?;
+        let points = scaled_points(&scene.options, curve, style)?;
+        let animation = &style.animation;
+        let local = if animation.rotate_from_deg == animation.rotate_to_deg {
+            let (sin, cos) = animation.rotate_from_deg.to_radians().sin_cos();
+            let rotated: Vec<Coordinate> = points.iter().map(|p| Coordinate { x: p.x * cos - p.y * sin, y: p.x * sin + p.y * cos }).collect();
+            BoundingBox::from_points(&rotated)
+        } else {
+            let radius = points.iter().map(|p| p.x.hypot(p.y)).fold(0.0, f64::max);
+            BoundingBox { min: Coordinate { x: -radius, y: -radius }, max: Coordinate { x: radius, y: radius } }
+        };
+        corners.push(Coordinate { x: curve.offset.x + local.min.x, y: curve.offset.y + local.min.y });
+        corners.push(Coordinate { x: curve.offset.x + local.max.x, y: curve.offset.y + local.max.y });
+    }
+    Ok(BoundingBox::from_points(&corners))
+}
+
+/// Returns the scene as it's drawn. With a margin, the canvas is resized around the
+/// combined bounds of the curves, which are moved so these bounds sit exactly at the
+/// canvas center; no percentage is involved, so every renderer agrees on the layout.
+pub(crate) fn fitted_scene<'a>(scene: &'a Scene, registry: &StyleRegistry) -> Result<Cow<'a, Scene>, RosettaError> {
+    let Some(margin) = scene.options.margin else {
+        return Ok(Cow::Borrowed(scene));
+    };
+    if scene.curves.is_empty() {
+        return Ok(Cow::Borrowed(scene));
+    }
+
+    let bounds = combined_bounds(scene, registry)?;
+    let center = bounds.center();
+    let mut fitted = scene.clone();
+    fitted.options.width = ((bounds.width() * CONTENT_SCALE + 2.0 * margin).ceil() as u32).max(1);
+    fitted.options.height = ((bounds.height() * CONTENT_SCALE + 2.0 * margin).ceil() as u32).max(1);
+    fitted.options.margin = None;
+    for curve in &mut fitted.curves {
+        curve.offset.x -= center.x;
+        curve.offset.y -= center.y;
+    }
+    debug!("fitted the canvas to {}x{}", fitted.options.width, fitted.options.height);
+    Ok(Cow::Owned(fitted))
+}
+
+/// Computes the duration after which every curve of the scene is back to its
+/// starting angle: the least common multiple of their durations, in seconds.
+/// A scene without curves never moves, so its loop lasts zero seconds.
+/// Durations with few common factors, e.g. 1.001s, 1.003s, 1.007s and so on, can loop
+/// after more milliseconds than a `u64` holds; such scenes are rejected.
+pub fn loop_duration(scene: &Scene, registry: &StyleRegistry) -> Result<f64, RosettaError> {
+    if scene.curves.is_empty() {
+        return Ok(0.0);
+    }
+    let mut loop_ms: u64 = 1;
+    for curve in &scene.curves {
+        let style = curve.style.resolve(registry)?;
+        let duration_ms = (parse_duration(&style.animation.duration)? * 1000.0).round().max(1.0) as u64;
+        loop_ms = (loop_ms / gcd(loop_ms, duration_ms))
+            .checked_mul(duration_ms)
+            .ok_or_else(|| RosettaError::InvalidStyle(String::from("the curve durations only loop after too long to count, use rounder durations")))?;
+    }
+    Ok(loop_ms as f64 / 1000.0)
+}
+
+/// Builds the SVG document of a scene as a typed tree, for callers willing to
+/// append their own nodes or change attributes before serializing it themselves.
+/// Every element of the content, from the style sheet to the curves and the overlay,
+/// is a node of the tree, so e.g. the curves can be looked up by their `id` or class.
+/// The tree is read back from the content `write_scene` writes, so both hold the same
+/// elements; content that can't be read back is reported as an `InvalidData` error.
+/// The tree is serialized in its own layout, so `minify` doesn't apply to it.
+#[cfg(feature = "svg")]
+pub fn svg_document(scene: &Scene, registry: &StyleRegistry) -> Result<svg::Document, RosettaError> {
+    use svg::node::element::tag::Type;
+    use svg::node::element::Element;
+    use svg::node::Text;
+    use svg::parser::Event;
+    use svg::Node;
+
+    let unreadable = |reason: String| RosettaError::Io(io::Error::new(io::ErrorKind::InvalidData, format!("can't read the document back: {}", reason)));
+    let scene = &*fitted_scene(scene, registry)?;
+    let mut content = Vec::new();
+    write_content(&mut content, scene, registry, None)?;
+    let content = String::from_utf8(content).map_err(|e| unreadable(e.to_string()))?;
+
+    let mut document = root_attributes(&scene.options)
+        .into_iter()
+        .fold(svg::Document::new(), |document, (name, value)| document.set(name, value));
+    // The content is read back element by element, stacking the ones still open.
+    let mut open: Vec<Element> = Vec::new();
+    for event in svg::Parser::new(&content) {
+        match event {
+            Event::Tag(name, kind, attributes) => {
+                let element = attributes.iter().fold(Element::new(name), |mut element, (name, value)| {
+                    element.assign(name.as_str(), unescape_xml(value));
+                    element
+                });
+                let element = match kind {
+                    Type::Start => {
+                        open.push(element);
+                        continue;
+                    }
+                    Type::End => open.pop().ok_or_else(|| unreadable(format!("</{}> closes no element", name)))?,
+                    Type::Empty => element,
+                };
+                match open.last_mut() {
+                    Some(parent) => parent.append(element),
+                    None => document.append(element),
+                }
+            }
+            Event::Text(text) if !text.trim().is_empty() => {
+                if let Some(parent) = open.last_mut() {
+                    parent.append(Text::new(unescape_xml(text)));
+                }
+            }
+            Event::Error(error) => return Err(unreadable(error.to_string())),
+            _ => {}
+        }
+    }
+    if !open.is_empty() {
+        return Err(unreadable(format!("{} elements are left open", open.len())));
+    }
+    Ok(document)
+}
+
+/// Writes everything inside the root element: styles, background, curves and overlay.
+/// A still frame at the given time drops the animations, the fading overlay and the hover rules.
+fn write_content(writer: &mut impl Write, scene: &Scene, registry: &StyleRegistry, frame: Option<f64>) -> Result<(), RosettaError> {
+    // Every value ends up in an attribute or a style sheet, so none is written unchecked.
+    scene.validate()?;
+    // A static document is written like the frame showing the curves at load.
+    let frame = match scene.options.mode {
+        RenderMode::Static => Some(frame.unwrap_or(0.0)),
+        RenderMode::Animated => frame,
+    };
+    let styles = scene
+        .curves
+        .iter()
+        .map(|curve| curve.style.resolve(registry).map(Cow::Borrowed))
+        .collect::<Result<Vec<_>, _>>()?;
+    // Named styles live in the registry, out of reach of `Scene::validate`.
+    for (i, (curve, style)) in scene.curves.iter().zip(&styles).enumerate() {
+        if let StyleRef::Named(_) = curve.style {
+            validate_style(style, &format!("curves[{}].style", i))?;
+        }
+    }
+    #[cfg(feature = "rand")]
+    let styles = match &scene.options.color_jitter {
+        Some(jitter) => jitter_styles(styles, jitter),
+        None => styles,
     };
-    let points = curve.compute_points();
-    write_path(writer, &points)?;
+
+    // Segmented curves are written as several paths, so they can't share a symbol.
+    let mut occurrences: HashMap<CurveKey, usize> = HashMap::new();
+    for (curve, style) in scene.curves.iter().zip(&styles).filter(|(_, style)| !style.pen.is_segmented()) {
+        *occurrences.entry(curve_key(curve, style)).or_default() += 1;
+    }
+
+    write_header(writer, &scene.options, frame.is_none())?;
+    if scene.options.interactive && frame.is_none() {
+        write_hover_style(writer)?;
+    }
+    if let Some(transform) = &scene.options.transform {
+        writeln!(writer, r#"    <g transform="{}">"#, transform)?;
+    }
+    write_background(writer, &scene.options)?;
+
+    // Writes the shared curves first, in scene order so the output is stable.
+    let mut symbols: HashMap<CurveKey, String> = HashMap::new();
+    for (curve, style) in scene.curves.iter().zip(&styles) {
+        let key = curve_key(curve, style);
+        if occurrences.get(&key).is_some_and(|&count| count > 1) && !symbols.contains_key(&key) {
+            let symbol_id = format!("curve_{}", symbols.len());
+            debug!("sharing {} identical curves as #{}", occurrences[&key], symbol_id);
+            write_symbol(writer, &symbol_id, &scaled_points(&scene.options, curve, style)?, &style.pen)?;
+            symbols.insert(key, symbol_id);
+        }
+    }
+
+    // Chained curves are drawn one after the other, in scene order.
+    let mut previous_draw: Option<String> = None;
+    let mut previous_end = 0.0;
+    for (i, (curve, style)) in scene.curves.iter().zip(&styles).enumerate() {
+        debug!("writing rosetta {} ({})", i, style.pen.color);
+        let symbol_id = if style.pen.is_segmented() { None } else { symbols.get(&curve_key(curve, style)).map(String::as_str) };
+        let draw_on = if style.animation.chain {
+            let draw_on = DrawOn {
+                id: format!("draw_{}", i),
+                begin: chained_begin(previous_draw.as_deref(), style.animation.delay.as_deref()),
+                start: previous_end + parse_delay(&style.animation)?,
+            };
+            previous_draw = Some(draw_on.id.clone());
+            previous_end = draw_on.start + parse_duration(&style.animation.duration)?;
+            Some(draw_on)
+        } else if style.animation.draw_in {
+            // Drawn all on its own, the curve starts like the first of a chain.
+            Some(DrawOn {
+                id: format!("draw_{}", i),
+                begin: chained_begin(None, style.animation.delay.as_deref()),
+                start: parse_delay(&style.animation)?,
+            })
+        } else {
+            None
+        };
+        let refs = CurveRefs { index: i, symbol_id, draw_on };
+        write_rosetta(writer, &scene.options, curve, style, &refs, frame)?;
+    }
+    if scene.options.transform.is_some() {
+        writeln!(writer, "    </g>")?;
+    }
+    if frame.is_none() {
+        write_footer(writer, &scene.options)?;
+    }
+    Ok(())
+}
+
+/// Applies the random color and opacity variations to the style of every curve.
+/// Each curve draws its hue shift, then its opacity change, whether or not its
+/// colors can be shifted, so that editing one curve leaves the others as they were.
+/// Colors that can't be parsed, such as CSS variables, keep their hue.
+#[cfg(feature = "rand")]
+fn jitter_styles<'a>(styles: Vec<Cow<'a, RosettaStyle>>, jitter: &JitterStyle) -> Vec<Cow<'a, RosettaStyle>> {
+    let mut rng = Pcg64::seed_from_u64(jitter.seed);
+    let mut spread = |range: f64| (2.0 * rng.random::<f64>() - 1.0) * range;
+    styles
+        .into_iter()
+        .map(|mut style| {
+            let (hue, opacity) = (spread(jitter.hue_range), spread(jitter.opacity_range));
+            let pen = &mut style.to_mut().pen;
+            pen.color = shift_hue(&pen.color, hue);
+            if let Some(segments) = &mut pen.segments {
+                segments.iter_mut().for_each(|color| *color = shift_hue(color, hue));
+            }
+            if let Some((start, end)) = &mut pen.gradient {
+                (*start, *end) = (shift_hue(start, hue), shift_hue(end, hue));
+            }
+            pen.opacity = (pen.opacity + opacity).clamp(0.0, 1.0);
+            style
+        })
+        .collect()
+}
+
+/// Wraps a writer to drop the newlines and the indentation starting each line.
+/// Every element of the document stands on its own lines, so this only removes
+/// whitespace between elements (and inside the CSS rules, where it's irrelevant).
+struct Minifier<W: Write> {
+    inner: W,         // Writer receiving the minified output.
+    line_start: bool, // Whether the next character begins a new line.
+}
+
+impl<W: Write> Write for Minifier<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut kept = Vec::with_capacity(buf.len());
+        for &byte in buf {
+            match byte {
+                b'\n' | b'\r' => self.line_start = true,
+                b' ' | b'\t' if self.line_start => {}
+                _ => {
+                    self.line_start = false;
+                    kept.push(byte);
+                }
+            }
+        }
+        self.inner.write_all(&kept)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Computes the points of a curve, scaled as placed on the scene.
+/// Quantizing snaps them in the curve's own frame, before the scene and curve transforms.
+fn scaled_points(options: &RenderOptions, curve: &SceneCurve, style: &RosettaStyle) -> Result<Vec<Coordinate>, RosettaError> {
+    let mut points = placed_curve(options, curve, style)?.compute_points();
+    if let Some(transform) = &options.transform_fn {
+        points.iter_mut().for_each(|p| *p = (transform.0)(*p));
+    }
+    if curve.scale != 1.0 {
+        for p in &mut points {
+            p.x *= curve.scale;
+            p.y *= curve.scale;
+        }
+    }
+    Ok(match options.quantize {
+        Some(step) => quantize(&points, step),
+        None => points,
+    })
+}
+
+/// Builds the curve of a style as placed in a scene, whose segments are about
+/// `options.segment_length` long on the canvas unless its step count is pinned.
+pub(crate) fn placed_curve(options: &RenderOptions, curve: &SceneCurve, style: &RosettaStyle) -> Result<Hypotrochoid, RosettaError> {
+    // Scaling the curve up stretches its segments, so they're measured unscaled.
+    style.geometry.curve_with_segments(options.segment_length / (curve.scale * CONTENT_SCALE))
+}
+
+/// Writes the path of a curve drawn several times as a reusable symbol.
+/// The stroke color is left to each `<use>` instance.
+fn write_symbol(writer: &mut impl Write, symbol_id: &str, points: &[Coordinate], pen: &Pen) -> io::Result<()> {
+    writeln!(writer, " <defs>")?;
+    writeln!(writer, r#"  <symbol id="{}" overflow="visible">"#, symbol_id)?;
+    write!(writer, r#"   <path id="{}_path" fill="none" stroke-width="{}" d="#, symbol_id, pen.width)?;
+    write_pen_path(writer, points, pen)?;
     writeln!(writer, r#"></path>"#)?;
-    writeln!(writer, r#"    <animateTransform attributeName="transform" attributeType="XML" type="rotate" from="0" to="360" dur="{}" repeatCount="indefinite" />"#, style.duration)?;
+    writeln!(writer, "  </symbol>")?;
+    writeln!(writer, " </defs>")?;
+    Ok(())
+}
+
+/// Identifies the one-shot animation drawing a chained or drawn-in curve on.
+struct DrawOn {
+    id: String,    // Id of the animation, referenced by the next chained curve.
+    begin: String, // SMIL begin value, e.g. `draw_0.end` to wait for the previous curve.
+    start: f64,    // Same start as a time after loading, in seconds, for CSS animations.
+}
+
+/// Computes when a chained curve starts drawing: at the end of the previous chained
+/// curve, or right away for the first one, both shifted by the curve delay if any.
+fn chained_begin(previous: Option<&str>, delay: Option<&str>) -> String {
+    match (previous, delay) {
+        (Some(previous), Some(delay)) if delay.starts_with('-') => format!("{}.end{}", previous, delay),
+        (Some(previous), Some(delay)) => format!("{}.end+{}", previous, delay),
+        (Some(previous), None) => format!("{}.end", previous),
+        (None, Some(delay)) => delay.to_string(),
+        (None, None) => String::from("0s"),
+    }
+}
+
+/// Gathers the ids a curve is written with, assigned while walking the scene.
+struct CurveRefs<'a> {
+    index: usize,               // Position of the curve in the scene.
+    symbol_id: Option<&'a str>, // Symbol holding the path shared with identical curves.
+    draw_on: Option<DrawOn>,    // One-shot animation of a chained curve.
+}
+
+/// Writes a single rosetta curve to the SVG file.
+/// Moved or labeled curves are wrapped in a group holding their position and caption.
+/// Shared curves reference their symbol instead of repeating the path data.
+fn write_rosetta(writer: &mut impl Write, options: &RenderOptions, curve: &SceneCurve, style: &RosettaStyle, refs: &CurveRefs, frame: Option<f64>) -> Result<(), RosettaError> {
+    let (symbol_id, draw_on) = (refs.symbol_id, refs.draw_on.as_ref());
+    let placed = curve.offset.x != 0.0 || curve.offset.y != 0.0 || curve.label.is_some();
+    // Interactive documents tell the curves apart by class, for the hover rules.
+    let classes = if options.interactive { format!(r#" class="curve curve_{}""#, refs.index) } else { String::new() };
+    writeln!(writer, r#"  <g id="rosettas"{} transform="translate({} {}) scale({})">"#, classes, options.width as f64 / 2.0, options.height as f64 / 2.0, CONTENT_SCALE)?;
+    if placed {
+        writeln!(writer, r#"  <g transform="translate({} {})">"#, curve.offset.x, curve.offset.y)?;
+    }
+    let angle = match frame {
+        Some(time) => rotation_at(&style.animation, time)?,
+        None if style.animation.phase != 0.0 => rotation_at(&style.animation, 0.0)?,
+        None => 0.0,
+    };
+    let css = options.animation_backend == AnimationBackend::Css && frame.is_none();
+    if css {
+        writeln!(writer, r#"    <g class="rosetta_{}" transform="rotate({})"{}>"#, refs.index, angle, stroke_opacity(&style.pen))?;
+    } else {
+        writeln!(writer, r#"    <g transform="rotate({})"{}>"#, angle, stroke_opacity(&style.pen))?;
+    }
+    let points = scaled_points(options, curve, style)?;
+    let animation = &style.animation;
+
+    // CSS rules are gathered while writing the curve, then written after it.
+    let mut rules = css.then(String::new);
+
+    // Text follows the path of the symbol when there's one, or gets its own path id.
+    let path_id = curve.text_on_path.as_ref().map(|_| match symbol_id {
+        Some(symbol_id) => format!("{}_path", symbol_id),
+        None => format!("curve_path_{}", refs.index),
+    });
+    if let Some(colors) = style.pen.arc_colors() {
+        write_segments(writer, options, &points, &colors, style, draw_on.filter(|_| frame.is_none()), rules.as_mut())?;
+        if let Some(path_id) = &path_id {
+            write!(writer, r#"      <defs><path id="{}" d="#, path_id)?;
+            write_pen_path(writer, &points, &style.pen)?;
+            writeln!(writer, r#"></path></defs>"#)?;
+        }
+    } else {
+        let color = stroke_color(options, &style.pen.color);
+        let element = if let Some(symbol_id) = symbol_id {
+            write!(writer, r##"      <use href="#{}" stroke="{}""##, symbol_id, color)?;
+            "use"
+        } else {
+            let id = path_id.as_ref().map(|path_id| format!(r#" id="{}""#, path_id)).unwrap_or_default();
+            write!(writer, r#"      <path{} fill="none" stroke-width="{}" stroke="{}" d="#, id, style.pen.width, color)?;
+            write_pen_path(writer, &points, &style.pen)?;
+            "path"
+        };
+        if (draw_on.is_some() || animation.draw_erase) && frame.is_none() {
+            match draw_on {
+                Some(draw_on) => write_draw_on(writer, polyline_length(&points), draw_on, &animation.duration, rules.as_mut())?,
+                None => write_draw_erase(writer, polyline_length(&points), refs.index, animation, rules.as_mut())?,
+            }
+            writeln!(writer, r#"      </{}>"#, element)?;
+        } else {
+            write_pen_attributes(writer, &style.pen)?;
+            writeln!(writer, r#"></{}>"#, element)?;
+        }
+    }
+    if let (Some(text), Some(path_id)) = (&curve.text_on_path, &path_id) {
+        writeln!(writer, r##"      <text fill="{}" font-family="{}" font-size="{}"><textPath href="#{}" startOffset="{}">{}</textPath></text>"##, text.color, escape_xml(&text.font_family), text.font_size, path_id, text.start_offset, escape_xml(&text.text))?;
+    }
+    match rules {
+        Some(rules) => write_css_animations(writer, refs.index, animation, style.pen.opacity, &rules)?,
+        None if frame.is_none() => {
+            write_rotation(writer, animation)?;
+            if let Some(twinkle) = &animation.twinkle {
+                write_twinkle(writer, twinkle, style.pen.opacity)?;
+            }
+        }
+        None => {}
+    }
     writeln!(writer, r#"    </g>"#)?;
+    if placed {
+        // The caption sits just below the circle swept by the rotating curve.
+        if let Some(label) = &curve.label {
+            let radius = points.iter().map(|p| p.x.hypot(p.y)).fold(0.0, f64::max);
+            writeln!(writer, r#"    <text y="{}" fill="white" font-family="sans-serif" font-size="{}" text-anchor="middle">{}</text>"#, radius + LABEL_SIZE, LABEL_SIZE, escape_xml(label))?;
+        }
+        writeln!(writer, r#"  </g>"#)?;
+    }
     writeln!(writer, r#"  </g>"#)?;
     Ok(())
 }
 
+/// Returns the color to stroke a curve with.
+/// Curves too close to the background color are lightened or darkened on request.
+fn stroke_color(options: &RenderOptions, color: &str) -> String {
+    match options.min_contrast {
+        Some(ratio) => ensure_contrast(color, &options.background_color, ratio),
+        None => color.to_string(),
+    }
+}
+
+/// Writes a curve split into equal arcs, each stroked with the next of `colors`.
+/// Consecutive arcs share their end point, and round caps hide the seams between them.
+/// The arcs keep plain colors, so the rainbow cycle turns all their hues together and
+/// a gradient keeps fading from its start color to its end color as it runs.
+/// A chained curve draws its arcs one after the other, each taking its share of the
+/// duration; drawing and erasing isn't supported along segments.
+fn write_segments(writer: &mut impl Write, options: &RenderOptions, points: &[Coordinate], colors: &[String], style: &RosettaStyle, draw_on: Option<&DrawOn>, mut rules: Option<&mut String>) -> Result<(), RosettaError> {
+    let last = points.len().saturating_sub(1);
+    let count = colors.len().min(last).max(1);
+    let duration = parse_duration(&style.animation.duration)?;
+    let total_length = polyline_length(points);
+    let mut begin = draw_on.map(|draw_on| draw_on.begin.clone());
+    let mut start = draw_on.map_or(0.0, |draw_on| draw_on.start);
+
+    for (k, color) in colors.iter().take(count).enumerate() {
+        let arc = &points[k * last / count..=(k + 1) * last / count];
+        write!(writer, r#"      <path fill="none" stroke-width="{}" stroke="{}" stroke-linecap="round" d="#, style.pen.width, stroke_color(options, color))?;
+        write_pen_path(writer, arc, &style.pen)?;
+        match (draw_on, &begin) {
+            (Some(draw_on), Some(arc_begin)) => {
+                // The last arc carries the id awaited by the next chained curve.
+                let length = polyline_length(arc);
+                let share = if total_length > 0.0 { length / total_length } else { 1.0 / count as f64 };
+                let id = if k + 1 == count { draw_on.id.clone() } else { format!("{}_{}", draw_on.id, k) };
+                let arc_draw_on = DrawOn { id: id.clone(), begin: arc_begin.clone(), start };
+                write_draw_on(writer, length, &arc_draw_on, &format!("{}s", duration * share), rules.as_deref_mut())?;
+                writeln!(writer, "      </path>")?;
+                begin = Some(format!("{}.end", id));
+                start += duration * share;
+            }
+            _ => {
+                write_pen_attributes(writer, &style.pen)?;
+                writeln!(writer, "></path>")?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns the stroke opacity attribute of a pen, empty for opaque strokes.
+/// It's set on the group holding the strokes rather than on the strokes themselves,
+/// which would override the twinkle animating the opacity on that group.
+fn stroke_opacity(pen: &Pen) -> String {
+    if pen.opacity != 1.0 { format!(r#" stroke-opacity="{}""#, pen.opacity) } else { String::new() }
+}
+
+/// Writes the dash pattern of a pen, if it has one.
+fn write_pen_attributes(writer: &mut impl Write, pen: &Pen) -> io::Result<()> {
+    if let Some(dash) = &pen.dash {
+        write!(writer, r#" stroke-dasharray="{}""#, dash)?;
+    }
+    Ok(())
+}
+
+/// Writes the dash attributes and animation drawing the curve on, then erasing it.
+/// The dash spans the whole curve, so the offset alone decides how much of it shows:
+/// the curve is drawn, held, erased back from its end, then stays blank for a while.
+/// With CSS, the stroke gets a class and its rules are appended to `rules` instead.
+fn write_draw_erase(writer: &mut impl Write, length: f64, index: usize, style: &Animation, rules: Option<&mut String>) -> io::Result<()> {
+    match rules {
+        Some(rules) => {
+            writeln!(writer, r#" class="rosetta_{0}_stroke" stroke-dasharray="{1}" stroke-dashoffset="{1}">"#, index, length)?;
+            rules.push_str(&format!(".rosetta_{0}_stroke {{ animation: rosetta_{0}_draw {1} linear infinite; }}\n", index, style.duration));
+            rules.push_str(&format!("@keyframes rosetta_{0}_draw {{ 0% {{ stroke-dashoffset: {1}; }} 40%, 50% {{ stroke-dashoffset: 0; }} 90%, 100% {{ stroke-dashoffset: {1}; }} }}\n", index, length));
+        }
+        None => {
+            writeln!(writer, r#" stroke-dasharray="{0}" stroke-dashoffset="{0}">"#, length)?;
+            writeln!(writer, r#"        <animate attributeName="stroke-dashoffset" values="{0};0;0;{0};{0}" keyTimes="0;0.4;0.5;0.9;1" dur="{1}" repeatCount="indefinite" />"#, length, style.duration)?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes the dash attributes and animation drawing a chained or drawn-in curve on, once and for good.
+/// The dash spans the whole length of the polyline, including its last segment back to the start
+/// of a closed curve. The drawing lasts one rotation, then the next chained curve starts from its `end` event.
+/// With CSS, which has no `end` events, the drawing is delayed by its start time instead.
+fn write_draw_on(writer: &mut impl Write, length: f64, draw_on: &DrawOn, duration: &str, rules: Option<&mut String>) -> io::Result<()> {
+    match rules {
+        Some(rules) => {
+            writeln!(writer, r#" class="{0}" stroke-dasharray="{1}" stroke-dashoffset="{1}">"#, draw_on.id, length)?;
+            rules.push_str(&format!(".{0} {{ animation: {0} {1} linear {2}s forwards; }}\n", draw_on.id, duration, draw_on.start));
+            rules.push_str(&format!("@keyframes {} {{ from {{ stroke-dashoffset: {}; }} to {{ stroke-dashoffset: 0; }} }}\n", draw_on.id, length));
+        }
+        None => {
+            writeln!(writer, r#" stroke-dasharray="{0}" stroke-dashoffset="{0}">"#, length)?;
+            writeln!(writer, r#"        <animate id="{}" attributeName="stroke-dashoffset" from="{}" to="0" dur="{}" begin="{}" fill="freeze" />"#, draw_on.id, length, duration, draw_on.begin)?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes the rotation animation of a rosetta curve.
+/// An alternating rotation goes from the start angle to the end angle and back again.
+fn write_rotation(writer: &mut impl Write, style: &Animation) -> Result<(), RosettaError> {
+    let begin = match &style.delay {
+        // A phase starts the rotation early, so that it's already partway through at load.
+        _ if style.phase != 0.0 => format!(r#" begin="{}s""#, parse_delay(style)? - phase_offset(style)?),
+        Some(delay) => format!(r#" begin="{}""#, delay),
+        None => String::new(),
+    };
+    if style.alternate {
+        writeln!(writer, r#"    <animateTransform attributeName="transform" attributeType="XML" type="rotate" values="{};{};{}" dur="{}"{} repeatCount="indefinite" />"#, style.rotate_from_deg, style.rotate_to_deg, style.rotate_from_deg, style.duration, begin)?;
+    } else {
+        writeln!(writer, r#"    <animateTransform attributeName="transform" attributeType="XML" type="rotate" from="{}" to="{}" dur="{}"{} repeatCount="indefinite" />"#, style.rotate_from_deg, style.rotate_to_deg, style.duration, begin)?;
+    }
+    Ok(())
+}
+
+/// Writes the stroke opacity animation of a twinkling curve.
+/// It's set on the rotating group, so the curve inherits it whatever its other animations.
+fn write_twinkle(writer: &mut impl Write, twinkle: &TwinkleStyle, opacity: f64) -> io::Result<()> {
+    let (low, high) = twinkle_range(twinkle, opacity);
+    writeln!(writer, r#"    <animate attributeName="stroke-opacity" values="{0};{1};{0}" dur="{2}" begin="{3}" repeatCount="indefinite" />"#, low, high, twinkle.duration, twinkle.begin)?;
+    Ok(())
+}
+
+/// Returns the lowest and highest stroke opacities of a twinkle, scaled by the pen opacity.
+fn twinkle_range(twinkle: &TwinkleStyle, opacity: f64) -> (f64, f64) {
+    (twinkle.min_opacity * opacity, opacity)
+}
+
+/// Writes the CSS animations of a curve: the rotation and twinkling of its group, set
+/// through its `rosetta_N` class, followed by the `rules` gathered for its strokes.
+/// CSS transforms of SVG elements turn around the origin of their parent, like `rotate()`.
+fn write_css_animations(writer: &mut impl Write, index: usize, style: &Animation, opacity: f64, rules: &str) -> Result<(), RosettaError> {
+    let delay = parse_delay(style)? - phase_offset(style)?;
+    let mut animations = vec![format!("rosetta_{}_rotate {} linear {}s infinite", index, style.duration, delay)];
+    if let Some(twinkle) = &style.twinkle {
+        animations.push(format!("rosetta_{}_twinkle {} linear {} infinite", index, twinkle.duration, twinkle.begin));
+    }
+
+    writeln!(writer, "    <style>")?;
+    writeln!(writer, "      .rosetta_{} {{ transform-origin: 0 0; animation: {}; }}", index, animations.join(", "))?;
+    if style.alternate {
+        writeln!(writer, "      @keyframes rosetta_{0}_rotate {{ 0%, 100% {{ transform: rotate({1}deg); }} 50% {{ transform: rotate({2}deg); }} }}", index, style.rotate_from_deg, style.rotate_to_deg)?;
+    } else {
+        writeln!(writer, "      @keyframes rosetta_{0}_rotate {{ from {{ transform: rotate({1}deg); }} to {{ transform: rotate({2}deg); }} }}", index, style.rotate_from_deg, style.rotate_to_deg)?;
+    }
+    if let Some(twinkle) = &style.twinkle {
+        let (low, high) = twinkle_range(twinkle, opacity);
+        writeln!(writer, "      @keyframes rosetta_{0}_twinkle {{ 0%, 100% {{ stroke-opacity: {1}; }} 50% {{ stroke-opacity: {2}; }} }}", index, low, high)?;
+    }
+    for rule in rules.lines() {
+        writeln!(writer, "      {}", rule)?;
+    }
+    writeln!(writer, "    </style>")?;
+    Ok(())
+}
+
+/// Parses the delay before a curve starts rotating, in seconds.
+fn parse_delay(style: &Animation) -> Result<f64, RosettaError> {
+    match &style.delay {
+        Some(delay) => parse_css_time(delay).ok_or_else(|| RosettaError::InvalidStyle(format!("invalid animation delay '{}'", delay))),
+        None => Ok(0.0),
+    }
+}
+
+/// Computes the rotation angle of a curve, in degrees, `time` seconds after loading.
+fn rotation_at(style: &Animation, time: f64) -> Result<f64, RosettaError> {
+    let delay = parse_delay(style)? - phase_offset(style)?;
+    let elapsed = (time - delay).max(0.0);
+    let progress = (elapsed / parse_duration(&style.duration)?).fract();
+
+    // An alternating rotation reaches the end angle halfway, then comes back.
+    let progress = if style.alternate { 1.0 - (2.0 * progress - 1.0).abs() } else { progress };
+    Ok(style.rotate_from_deg + (style.rotate_to_deg - style.rotate_from_deg) * progress)
+}
+
+/// Computes how long the rotation of a curve has already run at load, in seconds,
+/// from its phase: a phase of 90 degrees starts the curve a quarter into its cycle.
+fn phase_offset(style: &Animation) -> Result<f64, RosettaError> {
+    Ok(parse_duration(&style.duration)? * style.phase / 360.0)
+}
+
+/// Parses a CSS time value, i.e. a number followed by `s` or `ms`, into seconds.
+pub(crate) fn parse_css_time(value: &str) -> Option<f64> {
+    let (number, unit) = match value.strip_suffix("ms") {
+        Some(number) => (number, 0.001),
+        None => (value.strip_suffix('s')?, 1.0),
+    };
+    number.parse::<f64>().ok().filter(|n| n.is_finite()).map(|n| n * unit)
+}
+
+/// Parses an animation duration, rejecting anything but a positive CSS time value.
+fn parse_duration(value: &str) -> Result<f64, RosettaError> {
+    parse_css_time(value)
+        .filter(|seconds| *seconds > 0.0)
+        .ok_or_else(|| RosettaError::InvalidStyle(format!("invalid duration '{}', expected a time such as '6s'", value)))
+}
+
+/// Checks that a value is a list of SVG transform functions, e.g. `translate(100 50) rotate(30)`,
+/// each with a valid number of numeric arguments.
+pub(crate) fn is_svg_transform(value: &str) -> bool {
+    let mut rest = value.trim();
+    if rest.is_empty() {
+        return false;
+    }
+    while !rest.is_empty() {
+        let (Some(open), Some(close)) = (rest.find('('), rest.find(')')) else {
+            return false;
+        };
+        if close < open {
+            return false;
+        }
+        let arities: &[usize] = match rest[..open].trim() {
+            "matrix" => &[6],
+            "translate" | "scale" => &[1, 2],
+            "rotate" => &[1, 3],
+            "skewX" | "skewY" => &[1],
+            _ => return false,
+        };
+        let arguments: Vec<&str> = rest[open + 1..close]
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|argument| !argument.is_empty())
+            .collect();
+        if !arities.contains(&arguments.len()) || arguments.iter().any(|argument| argument.parse::<f64>().is_err()) {
+            return false;
+        }
+        rest = rest[close + 1..].trim_start_matches(|c: char| c == ',' || c.is_whitespace());
+    }
+    true
+}
+
+/// Escapes the characters that can't appear verbatim in XML text or in a quoted attribute.
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Reverts `escape_xml`, for values read back from the document.
+#[cfg(feature = "svg")]
+fn unescape_xml(text: &str) -> String {
+    text.replace("&quot;", "\"").replace("&gt;", ">").replace("&lt;", "<").replace("&amp;", "&")
+}
+
 /// Writes the SVG path data from a slice of coordinates.
 fn write_path(writer: &mut impl Write, points: &[Coordinate]) -> io::Result<()> {
-    if let Some(first_point) = points.first() {
-        write!(writer, r#"" M {},{}"#, first_point.x, first_point.y)?; // Moves the pen without drawing.
-        for point in points.iter().skip(1) {
-            write!(writer, " L {},{}", point.x, point.y)?; // Draws a line.
-        }
-         write!(writer, r#"""#)?
+    if !points.is_empty() {
+        write!(writer, r#"" {}""#, format_path_data(points, None, false))?;
+    }
+    Ok(())
+}
+
+/// Writes the SVG path data from a slice of coordinates, as Bézier curves through every point.
+/// Closed curves join up smoothly at their seam (see `format_smooth_path_data`).
+fn write_smooth_path(writer: &mut impl Write, points: &[Coordinate]) -> io::Result<()> {
+    if !points.is_empty() {
+        write!(writer, r#"" {}""#, format_smooth_path_data(points, None))?;
     }
     Ok(())
 }
+
+/// Writes the SVG path data of a curve drawn with the given pen, smoothed or not.
+/// Drawing animations measure the straight polyline, a close approximation of a smoothed one.
+fn write_pen_path(writer: &mut impl Write, points: &[Coordinate], pen: &Pen) -> io::Result<()> {
+    if pen.smooth {
+        write_smooth_path(writer, points)
+    } else {
+        write_path(writer, points)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_svg_writes_one_path_per_style() {
+        let styles = default_styles();
+        let svg = render_svg(&styles, &RenderOptions::default()).unwrap();
+        assert!(svg.contains("<svg"));
+        assert_eq!(svg.matches(r#"<path fill="none""#).count(), styles.len());
+    }
+
+    #[test]
+    fn text_on_path_quotes_font_families() {
+        let text = TextOnPath { font_family: String::from(r#""Fira Sans", serif"#), text: String::from("<3 & \"rosettas\""), ..Default::default() };
+        let scene = Scene::builder().add_curve(SceneCurve { text_on_path: Some(text), ..RosettaStyle::default().into() }).build();
+        let mut svg = Vec::new();
+        write_scene(&mut svg, &scene, &StyleRegistry::default()).unwrap();
+        let svg = String::from_utf8(svg).unwrap();
+        let document = roxmltree::Document::parse(&svg).unwrap();
+        let text = document.descendants().find(|node| node.has_tag_name("text")).unwrap();
+        assert_eq!(text.attribute("font-family"), Some(r#""Fira Sans", serif"#));
+
+        let text = TextOnPath { start_offset: String::from(r#"25%" onload="x"#), ..Default::default() };
+        let scene = Scene::builder().add_curve(SceneCurve { text_on_path: Some(text), ..RosettaStyle::default().into() }).build();
+        assert!(write_scene(&mut Vec::new(), &scene, &StyleRegistry::default()).is_err());
+    }
+
+    #[test]
+    fn render_svg_rejects_values_breaking_out_of_attributes() {
+        let mut styles = default_styles();
+        styles[0].animation.duration = String::from(r#"6 seconds" onload="x"#);
+        assert!(matches!(render_svg(&styles, &RenderOptions::default()), Err(RosettaError::InvalidField { .. })));
+
+        let mut styles = default_styles();
+        styles[0].pen.color = String::from(r#"rgb(1" onload="x)"#);
+        assert!(render_svg(&styles, &RenderOptions::default()).is_err());
+    }
+
+    #[test]
+    fn twinkle_scales_with_pen_opacity() {
+        let mut style = RosettaStyle::default();
+        style.pen.opacity = 0.8;
+        style.animation.twinkle = Some(TwinkleStyle { min_opacity: 0.5, ..Default::default() });
+        let svg = render_svg(&[style], &RenderOptions::default()).unwrap();
+        let document = roxmltree::Document::parse(&svg).unwrap();
+        assert!(document.descendants().filter(|node| node.has_tag_name("path")).all(|path| !path.has_attribute("stroke-opacity")));
+        let twinkle = document.descendants().find(|node| node.attribute("attributeName") == Some("stroke-opacity")).unwrap();
+        assert_eq!(twinkle.attribute("values"), Some("0.4;0.8;0.4"));
+        assert_eq!(twinkle.parent().unwrap().attribute("stroke-opacity"), Some("0.8"));
+    }
+
+    #[test]
+    fn unpinned_steps_follow_the_segment_length() {
+        let options = RenderOptions::default();
+        let mut style = RosettaStyle::default();
+        style.geometry.steps = None;
+        for scale in [0.5, 1.0, 2.0] {
+            let curve = SceneCurve { scale, ..style.clone().into() };
+            let points = scaled_points(&options, &curve, &style).unwrap();
+            let segment = polyline_length(&points) * CONTENT_SCALE / (points.len() - 1) as f64;
+            assert!((segment - options.segment_length).abs() < 0.05 * options.segment_length, "segments of {} at scale {}", segment, scale);
+        }
+
+        style.geometry.steps = Some(500);
+        let curve = style.clone().into();
+        assert_eq!(placed_curve(&options, &curve, &style).unwrap().steps(), 500);
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn svg_document_holds_every_element() {
+        let scene = Scene::builder().add_curve(RosettaStyle::default()).build();
+        let mut written = Vec::new();
+        write_scene(&mut written, &scene, &StyleRegistry::default()).unwrap();
+        let written = String::from_utf8(written).unwrap();
+        let tree = svg_document(&scene, &StyleRegistry::default()).unwrap().to_string();
+
+        let written = roxmltree::Document::parse(&written).unwrap();
+        let tree = roxmltree::Document::parse(&tree).unwrap();
+        let names = |document: &roxmltree::Document| document.descendants().filter(|node| node.is_element()).map(|node| node.tag_name().name().to_string()).collect::<Vec<_>>();
+        assert_eq!(names(&tree), names(&written));
+        let path = |document: &roxmltree::Document| document.descendants().find(|node| node.has_tag_name("path")).and_then(|path| path.attribute("d")).map(str::to_string);
+        assert_eq!(path(&tree), path(&written));
+        let style = |document: &roxmltree::Document| document.descendants().find(|node| node.has_tag_name("style")).and_then(|style| style.text()).map(|text| text.split_whitespace().collect::<Vec<_>>().join(" "));
+        assert_eq!(style(&tree), style(&written));
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn svg_document_keeps_escaped_text() {
+        let label = String::from(r#"<3 & "rosettas""#);
+        let scene = Scene::builder().add_curve(SceneCurve { label: Some(label.clone()), offset: Coordinate { x: 10.0, y: 0.0 }, ..RosettaStyle::default().into() }).build();
+        let tree = svg_document(&scene, &StyleRegistry::default()).unwrap().to_string();
+        let tree = roxmltree::Document::parse(&tree).unwrap();
+        let caption = tree.descendants().find(|node| node.has_tag_name("text")).unwrap();
+        assert_eq!(caption.text(), Some(label.as_str()));
+    }
+
+    /// Lists the rosetta strokes of a document, as their color and the points of their path.
+    fn curve_strokes(svg: &str) -> Vec<(String, Vec<Coordinate>)> {
+        let document = roxmltree::Document::parse(svg).unwrap();
+        document
+            .descendants()
+            .filter(|node| node.has_tag_name("path") && node.ancestors().any(|ancestor| ancestor.attribute("id") == Some("rosettas")))
+            .map(|path| {
+                let points = path.attribute("d").unwrap().split_whitespace().filter_map(|token| token.split_once(','));
+                let points = points.map(|(x, y)| Coordinate { x: x.parse().unwrap(), y: y.parse().unwrap() }).collect();
+                (path.attribute("stroke").unwrap().to_string(), points)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn segments_split_the_curve_into_arcs() {
+        let colors: Vec<String> = ["red", "orange", "yellow", "lime", "blue"].into_iter().map(String::from).collect();
+        let style = RosettaStyle { pen: Pen { segments: Some(colors.clone()), ..Default::default() }, ..Default::default() };
+        let options = RenderOptions::default();
+        let arcs = curve_strokes(&render_svg(std::slice::from_ref(&style), &options).unwrap());
+        assert_eq!(arcs.iter().map(|(color, _)| color.clone()).collect::<Vec<_>>(), colors);
+
+        // Consecutive arcs share their end point, so together they're as long as the curve.
+        let points = scaled_points(&options, &style.clone().into(), &style).unwrap();
+        assert!(arcs.windows(2).all(|pair| pair[0].1.last() == pair[1].1.first()));
+        let length: f64 = arcs.iter().map(|(_, arc)| polyline_length(arc)).sum();
+        assert!((length - polyline_length(&points)).abs() < 1e-6 * length, "{} against {}", length, polyline_length(&points));
+    }
+
+    #[test]
+    fn galleries_center_each_curve_in_its_own_file() {
+        let registry = StyleRegistry::default();
+        let offsets = [(-200.0, 0.0), (0.0, 150.0), (120.0, -80.0)];
+        let mut scene = Scene::default();
+        for (x, y) in offsets {
+            scene.curves.push(SceneCurve { offset: Coordinate { x, y }, ..RosettaStyle::default().into() });
+        }
+        let dir = std::env::temp_dir().join(format!("rustigraph_gallery_{}", std::process::id()));
+        render_gallery(&scene, &registry, &dir).unwrap();
+
+        let index = fs::read_to_string(dir.join("index.svg")).unwrap();
+        let document = roxmltree::Document::parse(&index).unwrap();
+        let images: Vec<_> = document.descendants().filter(|node| node.has_tag_name("image")).collect();
+        let hrefs: Vec<_> = images.iter().map(|image| image.attribute("href").unwrap()).collect();
+        assert_eq!(hrefs, ["curve_0.svg", "curve_1.svg", "curve_2.svg"]);
+        assert_eq!(images[2].attribute("y"), Some("500"));
+
+        let mut centered = Scene::default();
+        centered.curves.push(RosettaStyle::default().into());
+        let mut expected = Vec::new();
+        write_scene(&mut expected, &centered, &registry).unwrap();
+        for i in 0..offsets.len() {
+            let curve = fs::read_to_string(dir.join(format!("curve_{}.svg", i))).unwrap();
+            assert_eq!(curve.as_bytes(), expected, "curve_{}.svg isn't centered", i);
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn tiles_hold_a_single_plain_path() {
+        let options = RenderOptions::default();
+        let mut svg = Vec::new();
+        write_tiled(&mut svg, &RosettaStyle::default(), &options).unwrap();
+        let svg = String::from_utf8(svg).unwrap();
+        let document = roxmltree::Document::parse(&svg).unwrap();
+        let pattern = document.descendants().find(|node| node.has_tag_name("pattern")).unwrap();
+        assert_eq!(pattern.descendants().filter(|node| node.has_tag_name("path")).count(), 1);
+        assert!(document.descendants().any(|node| node.attribute("fill") == Some("url(#tile_pattern)")));
+
+        let segmented = Pen { segments: Some(vec![String::from("red"), String::from("blue")]), ..Default::default() };
+        let gradient = Pen { gradient: Some((String::from("red"), String::from("blue"))), ..Default::default() };
+        for pen in [segmented, gradient] {
+            let style = RosettaStyle { pen, ..Default::default() };
+            let error = write_tiled(&mut Vec::new(), &style, &options).unwrap_err();
+            assert_eq!(error.to_string(), "invalid style: can't tile a curve stroked in segments or a gradient");
+        }
+    }
+
+    #[test]
+    fn no_background_writes_nothing_over_the_color() {
+        let solid = RenderOptions { background: Background::Solid(String::from("#333")), ..Default::default() };
+        let solid = render_svg(&default_styles(), &solid).unwrap();
+        let none = RenderOptions { background: Background::None, ..Default::default() };
+        let none = render_svg(&default_styles(), &none).unwrap();
+        assert!(!none.contains("<pattern"));
+        assert_eq!(none.matches("<rect").count() + 1, solid.matches("<rect").count());
+        roxmltree::Document::parse(&none).unwrap();
+    }
+
+    #[test]
+    fn phases_start_curves_apart() {
+        let styles: Vec<RosettaStyle> = [0.0, 90.0, 180.0]
+            .into_iter()
+            .map(|phase| RosettaStyle { animation: Animation { duration: String::from("8s"), phase, ..Default::default() }, ..Default::default() })
+            .collect();
+        let svg = render_svg(&styles, &RenderOptions::default()).unwrap();
+        let document = roxmltree::Document::parse(&svg).unwrap();
+        let rotations: Vec<&str> = document.descendants().filter_map(|node| node.attribute("transform")).filter(|transform| transform.starts_with("rotate(")).collect();
+        assert_eq!(rotations, ["rotate(0)", "rotate(90)", "rotate(180)"]);
+        let begins: Vec<Option<&str>> = document.descendants().filter(|node| node.has_tag_name("animateTransform")).map(|node| node.attribute("begin")).collect();
+        assert_eq!(begins, [None, Some("-2s"), Some("-4s")]);
+    }
+
+    #[test]
+    fn empty_scenes_keep_the_background_and_overlay() {
+        let mut svg = Vec::new();
+        write_scene(&mut svg, &Scene::default(), &StyleRegistry::default()).unwrap();
+        let svg = String::from_utf8(svg).unwrap();
+        let document = roxmltree::Document::parse(&svg).unwrap();
+        assert!(document.descendants().any(|node| node.has_tag_name("pattern") && node.attribute("id") == Some("grid_pattern")));
+        assert!(document.descendants().any(|node| node.attribute("id") == Some("black-overlay")));
+        assert!(!document.descendants().any(|node| node.attribute("id") == Some("rosettas")));
+    }
+
+    #[test]
+    fn loop_duration_is_the_common_multiple_of_the_durations() {
+        let registry = StyleRegistry::default();
+        let scene = centered_scene(&default_styles(), &RenderOptions::default());
+        assert_eq!(loop_duration(&scene, &registry).unwrap(), 84.0);
+        assert_eq!(loop_duration(&Scene::default(), &registry).unwrap(), 0.0);
+
+        // The loop of these durations lasts longer than a `u64` can count in milliseconds.
+        let styles: Vec<RosettaStyle> = ["1.001s", "1.003s", "1.007s", "1.009s", "1.013s", "1.019s", "1.021s"]
+            .into_iter()
+            .map(|duration| RosettaStyle { animation: Animation { duration: String::from(duration), ..Default::default() }, ..Default::default() })
+            .collect();
+        let scene = centered_scene(&styles, &RenderOptions::default());
+        assert!(matches!(loop_duration(&scene, &registry), Err(RosettaError::InvalidStyle(_))));
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn jitter_follows_its_seed() {
+        let render = |jitter: Option<JitterStyle>| render_svg(&default_styles(), &RenderOptions { color_jitter: jitter, ..Default::default() }).unwrap();
+        let seeded = |seed| render(Some(JitterStyle { seed, ..Default::default() }));
+        // Lists the color of every curve, with the opacity of the group holding it.
+        let strokes = |svg: &str| -> Vec<(String, Option<String>)> {
+            let document = roxmltree::Document::parse(svg).unwrap();
+            document
+                .descendants()
+                .filter(|node| node.has_tag_name("path") && node.ancestors().any(|ancestor| ancestor.attribute("id") == Some("rosettas")))
+                .map(|path| (path.attribute("stroke").unwrap().to_string(), path.parent().unwrap().attribute("stroke-opacity").map(str::to_string)))
+                .collect()
+        };
+
+        assert_eq!(seeded(7), seeded(7));
+        let (first, second, plain) = (strokes(&seeded(7)), strokes(&seeded(8)), strokes(&render(None)));
+        assert_ne!(first, second);
+        assert_ne!(first, plain);
+        assert!(first.iter().zip(&plain).all(|((jittered, _), (color, _))| jittered != color), "{:?}", first);
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn random_styles_are_reproducible_valid_and_closed() {
+        let styles = random_styles(24, 42);
+        assert_eq!(styles.len(), 24);
+        assert_eq!(styles, random_styles(24, 42));
+        assert_ne!(styles, random_styles(24, 43));
+
+        let scene = centered_scene(&styles, &RenderOptions::default());
+        scene.validate().unwrap();
+        for style in &styles {
+            let stats = style.curve().unwrap().stats();
+            assert!(stats.required_revolutions.is_some());
+            assert!(stats.closure_error < crate::rosetta::CLOSE_TOLERANCE, "{:?} ends {} away from its start", style.geometry, stats.closure_error);
+        }
+    }
+
+    #[test]
+    fn duplicated_curves_share_a_symbol() {
+        let [cyan, gold, _] = <[RosettaStyle; 3]>::try_from(default_styles()).unwrap();
+        let red = RosettaStyle { pen: Pen { color: String::from("red"), ..Default::default() }, ..cyan.clone() };
+        let scene = Scene::builder()
+            .add_curve(cyan.clone())
+            .add_curve(SceneCurve { offset: Coordinate { x: 200.0, y: 0.0 }, ..red.into() })
+            .add_curve(gold.clone())
+            .build();
+        let mut svg = Vec::new();
+        write_scene(&mut svg, &scene, &StyleRegistry::default()).unwrap();
+        let svg = String::from_utf8(svg).unwrap();
+        let document = roxmltree::Document::parse(&svg).unwrap();
+        let count = |name: &str| document.descendants().filter(|node| node.has_tag_name(name)).count();
+        assert_eq!((count("symbol"), count("use")), (1, 2));
+
+        // Sharing changes nothing to the paths: without the duplicate, both curves are written inline.
+        let curve_paths = |document: &roxmltree::Document| -> Vec<String> {
+            document
+                .descendants()
+                .filter(|node| node.has_tag_name("path") && node.ancestors().any(|ancestor| ancestor.has_tag_name("symbol") || ancestor.attribute("id") == Some("rosettas")))
+                .filter_map(|path| path.attribute("d").map(str::to_string))
+                .collect()
+        };
+        let alone = render_svg(&[cyan, gold], &RenderOptions::default()).unwrap();
+        assert_eq!(curve_paths(&document), curve_paths(&roxmltree::Document::parse(&alone).unwrap()));
+    }
+
+    /// Concatenates the text of every `<style>` element of a document.
+    fn style_sheets(document: &roxmltree::Document) -> String {
+        document.descendants().filter(|node| node.has_tag_name("style")).filter_map(|node| node.text()).collect()
+    }
+
+    #[test]
+    fn static_documents_keep_the_glow_but_nothing_moving() {
+        let options = RenderOptions { mode: RenderMode::Static, ..Default::default() };
+        let svg = render_svg(&default_styles(), &options).unwrap();
+        let document = roxmltree::Document::parse(&svg).unwrap();
+        let smil = ["animate", "animateTransform", "animateMotion", "set"];
+        assert!(!document.descendants().any(|node| smil.iter().any(|name| node.has_tag_name(*name))));
+        assert!(!style_sheets(&document).contains("@keyframes"));
+        assert!(!document.descendants().any(|node| node.attribute("id") == Some("black-overlay")));
+
+        assert!(document.descendants().any(|node| node.has_tag_name("filter") && node.attribute("id") == Some("glow")));
+        assert!(style_sheets(&document).contains("filter: url(#glow)"));
+        assert_eq!(curve_strokes(&svg).len(), default_styles().len());
+    }
+
+    #[test]
+    fn css_animations_replace_smil() {
+        let options = RenderOptions { animation_backend: AnimationBackend::Css, ..Default::default() };
+        let svg = render_svg(&default_styles(), &options).unwrap();
+        let document = roxmltree::Document::parse(&svg).unwrap();
+        let smil = ["animate", "animateTransform", "animateMotion", "set"];
+        assert!(!document.descendants().any(|node| smil.iter().any(|name| node.has_tag_name(*name))));
+
+        let sheets = style_sheets(&document);
+        for index in 0..default_styles().len() {
+            assert!(sheets.contains(&format!("@keyframes rosetta_{}_rotate", index)), "curve {} doesn't rotate", index);
+            let class = format!("rosetta_{}", index);
+            assert!(document.descendants().any(|node| node.attribute("class") == Some(class.as_str())));
+        }
+
+        let smil_svg = render_svg(&default_styles(), &RenderOptions::default()).unwrap();
+        let smil_document = roxmltree::Document::parse(&smil_svg).unwrap();
+        let rotations = smil_document.descendants().filter(|node| node.has_tag_name("animateTransform")).count();
+        assert_eq!(rotations, default_styles().len());
+    }
+
+    #[test]
+    fn interactive_documents_dim_the_curves_not_hovered() {
+        let options = RenderOptions { interactive: true, ..Default::default() };
+        let svg = render_svg(&default_styles(), &options).unwrap();
+        let document = roxmltree::Document::parse(&svg).unwrap();
+        let sheets = style_sheets(&document);
+        assert!(sheets.contains(".curve { transition: opacity 0.3s; }"));
+        assert!(sheets.contains(&format!("svg:has(.curve:hover) .curve:not(:hover) {{ opacity: {}; }}", HOVER_DIMMED_OPACITY)));
+        let classes: Vec<_> = document.descendants().filter(|node| node.attribute("id") == Some("rosettas")).map(|node| node.attribute("class")).collect();
+        assert_eq!(classes, [Some("curve curve_0"), Some("curve curve_1"), Some("curve curve_2")]);
+
+        let still = render_svg(&default_styles(), &RenderOptions::default()).unwrap();
+        assert!(!still.contains(":hover") && !still.contains("class=\"curve"));
+    }
+
+    #[test]
+    fn drawn_in_curves_are_drawn_once() {
+        let mut style = RosettaStyle::default();
+        style.animation.draw_in = true;
+        style.animation.delay = Some(String::from("1.5s"));
+        let svg = render_svg(std::slice::from_ref(&style), &RenderOptions::default()).unwrap();
+        let document = roxmltree::Document::parse(&svg).unwrap();
+        let drawing: Vec<_> = document.descendants().filter(|node| node.has_tag_name("animate") && node.attribute("attributeName") == Some("stroke-dashoffset")).collect();
+        assert_eq!(drawing.len(), 1);
+        let path = drawing[0].parent().unwrap();
+        assert_eq!(drawing[0].attribute("from"), path.attribute("stroke-dasharray"));
+        assert_eq!((drawing[0].attribute("to"), drawing[0].attribute("begin"), drawing[0].attribute("fill")), (Some("0"), Some("1.5s"), Some("freeze")));
+        assert_eq!(drawing[0].attribute("repeatCount"), None);
+
+        let options = RenderOptions { animation_backend: AnimationBackend::Css, ..Default::default() };
+        let svg = render_svg(&[style.clone()], &options).unwrap();
+        let sheets = style_sheets(&roxmltree::Document::parse(&svg).unwrap());
+        assert!(sheets.contains(&format!(".draw_0 {{ animation: draw_0 {} linear 1.5s forwards; }}", style.animation.duration)), "{}", sheets);
+        assert!(!sheets.lines().any(|rule| rule.contains("draw_0") && rule.contains("infinite")));
+    }
+
+    #[test]
+    fn gradients_fade_from_their_start_to_their_end_color() {
+        let pen = Pen { gradient: Some((String::from("#ff0000"), String::from("#0000ff"))), ..Default::default() };
+        let style = RosettaStyle { pen, ..Default::default() };
+        let arcs = curve_strokes(&render_svg(&[style], &RenderOptions::default()).unwrap());
+        let colors: Vec<&str> = arcs.iter().map(|(color, _)| color.as_str()).collect();
+        assert_eq!(colors.len(), GRADIENT_ARCS);
+        assert_eq!((colors[0], colors[GRADIENT_ARCS - 1]), ("#ff0000", "#0000ff"));
+
+        // Red gives way to blue at every step.
+        let channels = |color: &str| (u8::from_str_radix(&color[1..3], 16).unwrap(), u8::from_str_radix(&color[5..7], 16).unwrap());
+        assert!(colors.windows(2).all(|pair| {
+            let ((red, blue), (next_red, next_blue)) = (channels(pair[0]), channels(pair[1]));
+            next_red < red && next_blue > blue
+        }));
+    }
+
+    #[test]
+    fn minified_documents_keep_every_element() {
+        let full = render_svg(&default_styles(), &RenderOptions::default()).unwrap();
+        let minified = render_svg(&default_styles(), &RenderOptions { minify: true, ..Default::default() }).unwrap();
+        assert!(minified.len() < full.len());
+        assert!(!minified.contains('\n'));
+
+        let elements = |svg: &str| -> Vec<(String, Vec<(String, String)>)> {
+            let document = roxmltree::Document::parse(svg).unwrap();
+            document
+                .descendants()
+                .filter(|node| node.is_element())
+                .map(|node| (node.tag_name().name().to_string(), node.attributes().map(|a| (a.name().to_string(), a.value().to_string())).collect()))
+                .collect()
+        };
+        assert_eq!(elements(&minified), elements(&full));
+    }
+
+    #[test]
+    fn units_size_the_document_but_not_the_view_box() {
+        let units = [(Units::Pixels, "200", "150"), (Units::Millimeters, "200mm", "150mm"), (Units::Inches, "200in", "150in"), (Units::Viewport, "100%", "100%")];
+        for (unit, width, height) in units {
+            let options = RenderOptions { width: 200, height: 150, units: unit, ..Default::default() };
+            let svg = render_svg(&default_styles(), &options).unwrap();
+            let document = roxmltree::Document::parse(&svg).unwrap();
+            let root = document.root_element();
+            assert_eq!((root.attribute("width"), root.attribute("height")), (Some(width), Some(height)), "{:?}", unit);
+            assert_eq!(root.attribute("viewBox"), Some("0 0 200 150"));
+        }
+    }
+
+    #[test]
+    fn chained_curves_start_where_the_previous_ones_end() {
+        let styles: Vec<RosettaStyle> = [("4s", Some("0.5s")), ("6s", Some("1s")), ("8s", Some("-2s")), ("5s", None)]
+            .into_iter()
+            .map(|(duration, delay)| {
+                let animation = Animation { duration: String::from(duration), delay: delay.map(String::from), chain: true, ..Default::default() };
+                RosettaStyle { animation, ..Default::default() }
+            })
+            .collect();
+        let svg = render_svg(&styles, &RenderOptions::default()).unwrap();
+        let document = roxmltree::Document::parse(&svg).unwrap();
+        let begins: Vec<_> = document.descendants().filter(|node| node.attribute("id").is_some_and(|id| id.starts_with("draw_"))).map(|node| node.attribute("begin").unwrap()).collect();
+        assert_eq!(begins, ["0.5s", "draw_0.end+1s", "draw_1.end-2s", "draw_2.end"]);
+
+        // Without end events, CSS adds up the durations and delays itself: 0.5, 0.5 + 4 + 1, 5.5 + 6 - 2, 9.5 + 8.
+        let options = RenderOptions { animation_backend: AnimationBackend::Css, ..Default::default() };
+        let svg = render_svg(&styles, &options).unwrap();
+        let sheets = style_sheets(&roxmltree::Document::parse(&svg).unwrap());
+        for (i, (duration, start)) in [("4s", 0.5), ("6s", 5.5), ("8s", 9.5), ("5s", 17.5)].into_iter().enumerate() {
+            let rule = format!(".draw_{0} {{ animation: draw_{0} {1} linear {2}s forwards; }}", i, duration, start);
+            assert!(sheets.contains(&rule), "missing {}", rule);
+        }
+    }
+
+    #[test]
+    fn fragments_omit_the_xml_prolog() {
+        let standalone = render_svg(&default_styles(), &RenderOptions::default()).unwrap();
+        let fragment = render_svg(&default_styles(), &RenderOptions { standalone: false, ..Default::default() }).unwrap();
+        assert!(standalone.starts_with(r#"<?xml version="1.0" encoding="UTF-8"?>"#));
+        assert!(!fragment.contains("<?xml"));
+        assert!(fragment.trim_start().starts_with("<svg "));
+        assert_eq!(standalone.lines().skip(1).collect::<Vec<_>>(), fragment.lines().collect::<Vec<_>>());
+        roxmltree::Document::parse(&fragment).unwrap();
+    }
+
+    #[test]
+    fn margins_fit_the_canvas_around_the_curves() {
+        let registry = StyleRegistry::default();
+        let mut scene = Scene { options: RenderOptions { margin: Some(20.0), ..Default::default() }, ..Default::default() };
+        scene.curves.push(SceneCurve { offset: Coordinate { x: -150.0, y: 40.0 }, ..RosettaStyle::default().into() });
+        scene.curves.push(SceneCurve { offset: Coordinate { x: 90.0, y: 0.0 }, scale: 0.5, ..RosettaStyle::default().into() });
+        let bounds = combined_bounds(&scene, &registry).unwrap();
+
+        let mut svg = Vec::new();
+        write_scene(&mut svg, &scene, &registry).unwrap();
+        let svg = String::from_utf8(svg).unwrap();
+        let document = roxmltree::Document::parse(&svg).unwrap();
+        let (width, height) = ((bounds.width() * CONTENT_SCALE + 40.0).ceil(), (bounds.height() * CONTENT_SCALE + 40.0).ceil());
+        assert_eq!(document.root_element().attribute("viewBox"), Some(format!("0 0 {} {}", width, height).as_str()));
+
+        // The curves are moved so their bounds sit at the center of the new canvas.
+        let fitted = fitted_scene(&scene, &registry).unwrap();
+        let center = combined_bounds(&fitted, &registry).unwrap().center();
+        assert!(center.x.abs() < 1e-9 && center.y.abs() < 1e-9, "{:?}", center);
+        assert_eq!(fitted.options.margin, None);
+    }
+}
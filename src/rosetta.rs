@@ -1,69 +1,1143 @@
-use std::f64::consts::PI;
+#[cfg(feature = "std")]
+use crate::error::RosettaError;
+use crate::geometry::{distance_to_segment, polyline_length, BoundingBox};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::f64::consts::PI;
+use core::fmt::{self, Write};
+use log::{debug, warn};
+// Test builds link `std`, whose inherent float methods take over.
+#[cfg(not(any(feature = "std", test)))]
+use num_traits::Float;
+
+/// Maximum number of revolutions a curve may need to close.
+pub const MAX_REVOLUTIONS: u64 = 1000;
+
+/// Number of revolutions drawn for curves that don't close within `MAX_REVOLUTIONS`.
+pub const FALLBACK_REVOLUTIONS: u64 = 16;
+
+/// Maximum number of decimal digits of the radii taken into account to detect closure.
+const RADIUS_PRECISION: i32 = 3;
+
+/// Number of steps from which the `rayon` feature samples a curve on several threads.
+/// Below it, handing the samples out costs more than computing them.
+#[cfg(feature = "rayon")]
+const PARALLEL_MIN_STEPS: usize = 10_000;
+
+/// Largest gap between the first and last points of a curve still considered closed.
+pub const CLOSE_TOLERANCE: f64 = 1e-3;
+
+/// Number of evenly spaced intervals per revolution that adaptive sampling starts from.
+const ADAPTIVE_SEEDS_PER_REVOLUTION: usize = 16;
+
+/// Maximum number of times adaptive sampling halves a seed interval.
+const MAX_SUBDIVISION_DEPTH: u32 = 12;
+
+/// Number of steps per revolution of the coarse pass estimating the length of a curve.
+const COARSE_STEPS_PER_REVOLUTION: usize = 256;
+
+/// Fewest and most steps `adaptive_steps` picks, whatever the size of the curve.
+const MIN_ADAPTIVE_STEPS: usize = 64;
+const MAX_ADAPTIVE_STEPS: usize = 100_000;
 
 // A 2D coordinate in cartesian space.
-#[derive(Debug, Clone, Copy)]
+// Serialized as a compact `[x, y]` array, which keeps point dumps small.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "[f64; 2]", into = "[f64; 2]"))]
 pub struct Coordinate {
     pub x: f64,
     pub y: f64,
 }
 
+impl From<[f64; 2]> for Coordinate {
+    fn from([x, y]: [f64; 2]) -> Self {
+        Coordinate { x, y }
+    }
+}
+
+impl From<Coordinate> for [f64; 2] {
+    fn from(p: Coordinate) -> Self {
+        [p.x, p.y]
+    }
+}
+
+// A complex number, standing for the point `re + i * im` of the plane.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Complex {
+    pub re: f64,
+    pub im: f64,
+}
+
+// A parametric curve traced by a drawing pen as the angle theta grows.
+pub trait Curve {
+    /// Computes a single point on the curve for a given angle theta.
+    fn generate_point(&self, theta: f64) -> Coordinate;
+
+    /// Computes the derivative of the pen position with respect to theta.
+    fn tangent(&self, theta: f64) -> Coordinate;
+
+    /// Returns the number of steps (points) used to approximate the curve.
+    fn steps(&self) -> usize;
+
+    /// Returns the number of full turns of theta needed to trace the curve.
+    fn revolutions(&self) -> f64;
+
+    /// Returns the number of points produced by `compute_points` and `points_iter`.
+    /// `compute_points` reserves exactly this capacity, so its buffer never reallocates;
+    /// `adaptive_sample` picks its own number of points, which this doesn't predict.
+    fn point_count(&self) -> usize {
+        self.steps() + 1
+    }
+
+    /// Iterates over the points of the curve as they're sampled, without recentering them.
+    /// Unlike `compute_points`, nothing is allocated, which suits `no_std` targets.
+    fn points_iter(&self) -> Samples<'_, Self> {
+        Samples {
+            curve: self,
+            next: 0,
+            steps: self.steps(),
+            revolutions: self.revolutions(),
+            offset: Coordinate { x: 0.0, y: 0.0 },
+        }
+    }
+
+    /// Computes the box enclosing the raw points, sampling the curve without storing them.
+    /// Its extent tells how much room the curve takes before it's rendered, e.g. to lay out
+    /// several curves side by side; `compute_points` recenters the points on its center.
+    fn bounding_box(&self) -> BoundingBox {
+        self.points_iter().collect()
+    }
+
+    /// Iterates over the points of the curve recentered exactly like `compute_points` does,
+    /// without storing them, e.g. to stream path data straight to a writer. The curve is
+    /// sampled twice, once by `bounding_box` to find its center, then as it's iterated.
+    fn centered_points_iter(&self) -> Samples<'_, Self> {
+        let center = self.bounding_box().center();
+        Samples { offset: center, ..self.points_iter() }
+    }
+
+    /// Computes all the points of the curve and recenters them.
+    /// The result is an array of coordinates centered around the origin.
+    fn compute_points(&self) -> Vec<Coordinate> {
+        sample_points(self)
+    }
+
+    /// Computes points of the curve, denser where it bends and sparser where it's nearly
+    /// straight, so that no chord strays more than `max_chord_error` from the true curve.
+    /// The points are recentered like those of `compute_points`.
+    fn adaptive_sample(&self, max_chord_error: f64) -> Vec<Coordinate> {
+        let revolutions = self.revolutions();
+        let seeds = (revolutions.ceil().max(1.0) as usize) * ADAPTIVE_SEEDS_PER_REVOLUTION;
+        let mut points = vec![self.generate_point(0.0)];
+
+        // Starts from a few intervals per revolution, so that a whole loop whose
+        // midpoint happens to lie on its chord can't be skipped.
+        for j in 0..seeds {
+            let start = sample_angle(j, seeds, revolutions);
+            let end = sample_angle(j + 1, seeds, revolutions);
+            let first = *points.last().unwrap();
+            subdivide(self, (start, first), (end, self.generate_point(end)), max_chord_error, 0, &mut points);
+        }
+        debug!("adaptively sampled {} points over {} revolutions", points.len(), revolutions);
+
+        recenter(&mut points);
+        points
+    }
+
+    /// Picks a number of steps giving segments about `target_segment_len` long, so that
+    /// small and large curves look equally smooth. The length of the curve is estimated
+    /// over all its revolutions by a coarse pass, and the result is kept between
+    /// `MIN_ADAPTIVE_STEPS` and `MAX_ADAPTIVE_STEPS`.
+    fn adaptive_steps(&self, target_segment_len: f64) -> usize {
+        let revolutions = self.revolutions();
+        let coarse = (revolutions.ceil().max(1.0) as usize) * COARSE_STEPS_PER_REVOLUTION;
+        let points: Vec<Coordinate> = (0..=coarse).map(|j| self.generate_point(sample_angle(j, coarse, revolutions))).collect();
+
+        // A NaN count, from a non-finite length or target, saturates to zero.
+        let steps = (polyline_length(&points) / target_segment_len).ceil() as usize;
+        steps.clamp(MIN_ADAPTIVE_STEPS, MAX_ADAPTIVE_STEPS)
+    }
+
+    /// Computes `n` evenly spaced points of the curve from angle `theta_a` to `theta_b`,
+    /// both included, so segments sharing an end angle join without a gap.
+    /// The points are left where `generate_point` puts them, without recentering.
+    fn sample_segment(&self, theta_a: f64, theta_b: f64, n: usize) -> Vec<Coordinate> {
+        match n {
+            0 => Vec::new(),
+            1 => vec![self.generate_point(theta_a)],
+            _ => (0..n)
+                .map(|j| {
+                    // The last angle is exactly `theta_b`, whatever the rounding of the steps.
+                    let t = j as f64 / (n - 1) as f64;
+                    self.generate_point(if j == n - 1 { theta_b } else { theta_a + (theta_b - theta_a) * t })
+                })
+                .collect(),
+        }
+    }
+
+    /// Computes the pen speed (the magnitude of the tangent) at each sampled point,
+    /// normalized so that the fastest point has a speed of 1.
+    /// Can be fed to SVG `keyTimes` so that a moving dot follows the real pen pace.
+    fn speed_profile(&self) -> Vec<f64> {
+        let (steps, revolutions) = (self.steps(), self.revolutions());
+        let mut speeds = Vec::with_capacity(self.point_count());
+        for j in 0..=steps {
+            let tangent = self.tangent(sample_angle(j, steps, revolutions));
+            speeds.push(tangent.x.hypot(tangent.y));
+        }
+
+        let max_speed = speeds.iter().copied().fold(0.0, f64::max);
+        if max_speed > 0.0 {
+            for speed in &mut speeds {
+                *speed /= max_speed;
+            }
+        }
+        speeds
+    }
+
+    /// Returns the SVG path data of the recentered curve, e.g. `M 1.00,0.00 L 0.00,1.00 Z`,
+    /// without any wrapping element or styling, to feed another SVG or canvas pipeline.
+    /// Coordinates are written with `precision` decimals, and `closed` ends the path
+    /// with `Z`, joining the last point back to the first.
+    ///
+    /// ```
+    /// use rustigraph::rosetta::{Curve, CurveKind, Hypotrochoid};
+    ///
+    /// // The three cusps of a deltoid, the rolling circle being a third of the fixed one.
+    /// let deltoid = Hypotrochoid::new(3.0, 1.0, 1.0, 3, CurveKind::Hypotrochoid)?;
+    /// assert_eq!(deltoid.path_data(2, true), "M 2.25,0.00 L -2.25,2.60 L -2.25,-2.60 L 2.25,0.00 Z");
+    /// # Ok::<(), rustigraph::error::RosettaError>(())
+    /// ```
+    fn path_data(&self, precision: usize, closed: bool) -> String {
+        format_path_data(&self.compute_points(), Some(precision), closed)
+    }
+
+    /// Returns the SVG path data of the recentered curve as Bézier curves through every point,
+    /// e.g. `M 1.00,0.00 C 1.00,0.17 0.17,1.00 0.00,1.00 ...`, smooth even with few steps.
+    fn smooth_path_data(&self, precision: usize) -> String {
+        format_smooth_path_data(&self.compute_points(), Some(precision))
+    }
+
+    /// Computes the bounding box of the recentered curve, without storing its points.
+    fn bounds(&self) -> BoundingBox {
+        let raw = self.bounding_box();
+        let (min, max, center) = (raw.min, raw.max, raw.center());
+        BoundingBox {
+            min: Coordinate { x: min.x - center.x, y: min.y - center.y },
+            max: Coordinate { x: max.x - center.x, y: max.y - center.y },
+        }
+    }
+
+    /// Computes the uniform scale factor making the curve fit exactly inside a
+    /// `width` x `height` area, keeping `margin` units free on every side.
+    fn scale_to_fit(&self, width: f64, height: f64, margin: f64) -> f64 {
+        let bounds = self.bounds();
+        let scale_x = (width - 2.0 * margin) / bounds.width();
+        let scale_y = (height - 2.0 * margin) / bounds.height();
+
+        // A flat curve only constrains the scale along its non-zero extent.
+        match (bounds.width() > 0.0, bounds.height() > 0.0) {
+            (true, true) => scale_x.min(scale_y),
+            (true, false) => scale_x,
+            (false, true) => scale_y,
+            (false, false) => {
+                debug!("curve is a single point; keeping its original scale");
+                1.0
+            }
+        }
+    }
+}
+
+// An iterator over the evenly spaced samples of a curve, from theta = 0 onwards.
+pub struct Samples<'a, C: Curve + ?Sized> {
+    curve: &'a C,       // Curve being sampled.
+    next: usize,        // Index of the next sample.
+    steps: usize,       // Number of steps between the first and the last sample.
+    revolutions: f64,   // Number of full turns of theta covered by the samples.
+    offset: Coordinate, // Subtracted from every sample, to recenter the curve.
+}
+
+impl<C: Curve + ?Sized> Iterator for Samples<'_, C> {
+    type Item = Coordinate;
+
+    fn next(&mut self) -> Option<Coordinate> {
+        if self.next > self.steps {
+            return None;
+        }
+        let point = self.curve.generate_point(sample_angle(self.next, self.steps, self.revolutions));
+        self.next += 1;
+        Some(Coordinate { x: point.x - self.offset.x, y: point.y - self.offset.y })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = (self.steps + 1).saturating_sub(self.next);
+        (remaining, Some(remaining))
+    }
+}
+
+impl<C: Curve + ?Sized> ExactSizeIterator for Samples<'_, C> {}
+
+/// Appends the points of the curve between angles `a` and `b`, excluding the one at `a`.
+/// Halves the interval as long as the curve strays more than `tolerance` from its chord.
+fn subdivide<C: Curve + ?Sized>(curve: &C, (a, pa): (f64, Coordinate), (b, pb): (f64, Coordinate), tolerance: f64, depth: u32, points: &mut Vec<Coordinate>) {
+    let mid = (a + b) / 2.0;
+    let pm = curve.generate_point(mid);
+
+    // Probes the quarters as well, as an S-shaped arc crosses its chord at the midpoint.
+    let error = [pm, curve.generate_point((a + mid) / 2.0), curve.generate_point((mid + b) / 2.0)]
+        .into_iter()
+        .map(|p| distance_to_segment(p, pa, pb))
+        .fold(0.0, f64::max);
+
+    // A non-finite error can't shrink, so such intervals are left as they are.
+    if depth >= MAX_SUBDIVISION_DEPTH || error.is_nan() || error <= tolerance {
+        points.push(pb);
+    } else {
+        subdivide(curve, (a, pa), (mid, pm), tolerance, depth + 1, points);
+        subdivide(curve, (mid, pm), (b, pb), tolerance, depth + 1, points);
+    }
+}
+
+/// Computes all the points of the curve, one after the other, and recenters them.
+fn sample_points<C: Curve + ?Sized>(curve: &C) -> Vec<Coordinate> {
+    let revolutions = curve.revolutions();
+    let mut points = Vec::with_capacity(curve.point_count());
+    debug!("sampling {} points over {} revolutions", curve.point_count(), revolutions);
+
+    // Computes raw points.
+    points.extend(curve.points_iter());
+    if points.iter().any(|p| !p.x.is_finite() || !p.y.is_finite()) {
+        warn!("curve produced non-finite coordinates; check its parameters");
+    }
+
+    recenter(&mut points);
+    points
+}
+
+/// Computes all the points of the curve and recenters them, spreading the sampling and
+/// the bounding box over every core. Samples are collected by index and min/max don't
+/// depend on their order, so the points are bit for bit those of `sample_points`.
+#[cfg(feature = "rayon")]
+fn par_sample_points<C: Curve + Sync + ?Sized>(curve: &C) -> Vec<Coordinate> {
+    use rayon::prelude::*;
+
+    if curve.steps() < PARALLEL_MIN_STEPS {
+        return sample_points(curve);
+    }
+    let (steps, revolutions) = (curve.steps(), curve.revolutions());
+    debug!("sampling {} points over {} revolutions in parallel", curve.point_count(), revolutions);
+
+    // Computes raw points.
+    let mut points: Vec<Coordinate> = (0..=steps)
+        .into_par_iter()
+        .map(|j| curve.generate_point(sample_angle(j, steps, revolutions)))
+        .collect();
+    if points.par_iter().any(|p| !p.x.is_finite() || !p.y.is_finite()) {
+        warn!("curve produced non-finite coordinates; check its parameters");
+    }
+
+    let offset = points
+        .par_iter()
+        .fold(|| BoundingBox::EMPTY, |bounds, p| bounds.including(*p))
+        .reduce(|| BoundingBox::EMPTY, BoundingBox::union)
+        .center();
+    points.par_iter_mut().for_each(|p| {
+        p.x -= offset.x;
+        p.y -= offset.y;
+    });
+    points
+}
+
+/// Computes the box enclosing the raw points of the curve, sampling them on every core
+/// without storing them.
+#[cfg(feature = "rayon")]
+fn par_bounding_box<C: Curve + Sync + ?Sized>(curve: &C) -> BoundingBox {
+    use rayon::prelude::*;
+
+    let (steps, revolutions) = (curve.steps(), curve.revolutions());
+    if steps < PARALLEL_MIN_STEPS {
+        return curve.points_iter().collect();
+    }
+    (0..=steps)
+        .into_par_iter()
+        .fold(|| BoundingBox::EMPTY, |bounds, j| bounds.including(curve.generate_point(sample_angle(j, steps, revolutions))))
+        .reduce(|| BoundingBox::EMPTY, BoundingBox::union)
+}
+
+/// Shifts the points so that their bounding box is centered around the origin.
+fn recenter(points: &mut [Coordinate]) {
+    let offset = BoundingBox::from_points(points).center();
+    for p in points {
+        p.x -= offset.x;
+        p.y -= offset.y;
+    }
+}
+
+/// Formats points as SVG path data: a move to the first point, then a line to each next one.
+/// Coordinates keep their shortest round-trip text unless a number of decimals is given.
+pub(crate) fn format_path_data(points: &[Coordinate], precision: Option<usize>, closed: bool) -> String {
+    let mut data = String::new();
+    for (i, point) in points.iter().enumerate() {
+        data.push_str(if i == 0 { "M " } else { " L " });
+        push_coordinate(&mut data, *point, precision);
+    }
+    if closed && !points.is_empty() {
+        data.push_str(" Z");
+    }
+    data
+}
+
+/// Formats points as SVG path data made of cubic Bézier curves passing through every point,
+/// their control points following the Catmull-Rom spline of the points: far fewer points
+/// give a smooth curve. A closed polyline, whose last point comes back onto the first,
+/// wraps around its seam so that it doesn't kink there, while an open one starts and
+/// ends in the direction of its end segments.
+pub(crate) fn format_smooth_path_data(points: &[Coordinate], precision: Option<usize>) -> String {
+    let n = points.len();
+    if n < 3 {
+        return format_path_data(points, precision, false);
+    }
+    let (first, last) = (points[0], points[n - 1]);
+    let closed = (last.x - first.x).hypot(last.y - first.y) <= CLOSE_TOLERANCE;
+
+    // Neighbors before the first point and after the last one, as the spline needs them.
+    let at = |i: isize| {
+        if closed {
+            points[i.rem_euclid(n as isize - 1) as usize]
+        } else {
+            points[i.clamp(0, n as isize - 1) as usize]
+        }
+    };
+    let mut data = String::from("M ");
+    push_coordinate(&mut data, first, precision);
+    for i in 0..n as isize - 1 {
+        let (p0, p1, p2, p3) = (at(i - 1), at(i), at(i + 1), at(i + 2));
+        let c1 = Coordinate { x: p1.x + (p2.x - p0.x) / 6.0, y: p1.y + (p2.y - p0.y) / 6.0 };
+        let c2 = Coordinate { x: p2.x - (p3.x - p1.x) / 6.0, y: p2.y - (p3.y - p1.y) / 6.0 };
+        for (j, point) in [c1, c2, points[i as usize + 1]].into_iter().enumerate() {
+            data.push_str(if j == 0 { " C " } else { " " });
+            push_coordinate(&mut data, point, precision);
+        }
+    }
+    data
+}
+
+/// Appends a point as `x,y`, keeping the shortest round-trip text unless a number of decimals is given.
+fn push_coordinate(data: &mut String, point: Coordinate, precision: Option<usize>) {
+    let written = match precision {
+        Some(precision) => write!(data, "{:.*},{:.*}", precision, point.x, precision, point.y),
+        None => write!(data, "{},{}", point.x, point.y),
+    };
+    written.expect("writing to a string never fails");
+}
+
+/// Computes the angle theta of the j-th of `steps` evenly spaced samples.
+fn sample_angle(j: usize, steps: usize, revolutions: f64) -> f64 {
+    2.0 * PI * (j as f64) / (steps as f64) * revolutions
+}
+
+// Selects on which side of the fixed circle the other one rolls.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CurveKind {
+    #[default]
+    Hypotrochoid, // Rolls inside the fixed circle, tracing loops pointing inwards.
+    Epitrochoid,  // Rolls outside the fixed circle, tracing petals around it.
+}
+
 // A mathematical description of a rosetta (specifically, a hypotrochoid),
 // formed by tracing a point attached to a circle rolling inside another circle.
+// With the `Epitrochoid` kind, the circle rolls outside the fixed one instead:
+// revolutions, loops and petals are counted with the same formulas.
+//
+// The equations also hold when the rolling circle is the larger one
+// (`inner_radius > outer_radius`): it then encloses the fixed circle, like a ring
+// rolling around a pin, and traces pericycloid-like curves whose loops point outwards.
+// The points stay finite and are recentered like any other hypotrochoid.
 #[derive(Debug, Clone, Copy)]
 pub struct Hypotrochoid {
     pub outer_radius: f64, // Radius of the fixed outer circle.
     pub inner_radius: f64, // Radius of the rolling inner circle.
     pub pen_offset: f64,   // From the center of the inner circle to the drawing point.
     pub steps: usize,      // Number of steps (points) used to approximate the curve.
+    pub kind: CurveKind,   // Side of the fixed circle the other one rolls on.
 }
 
-impl Hypotrochoid {
+impl Curve for Hypotrochoid {
     /// Computes a single point on the hypotrochoid curve for a given angle theta.
     /// Uses the standard parametric equation of a hypotrochoid. 
+    /// Both `r_diff` and `ratio` turn negative for a pericycloid, which simply
+    /// reverses the direction in which the rolling circle turns.
+    /// An epitrochoid uses `x = (R+r)·cos θ − d·cos((R+r)/r · θ)`, `y = (R+r)·sin θ − d·sin((R+r)/r · θ)`.
     fn generate_point(&self, theta: f64) -> Coordinate {
-        let r_diff = self.outer_radius - self.inner_radius;
-        let ratio = r_diff / self.inner_radius;
+        match self.kind {
+            CurveKind::Hypotrochoid => {
+                let r_diff = self.outer_radius - self.inner_radius;
+                let ratio = r_diff / self.inner_radius;
+
+                Coordinate {
+                    x: r_diff * theta.cos() + self.pen_offset * (ratio * theta).cos(),
+                    y: r_diff * theta.sin() - self.pen_offset * (ratio * theta).sin(),
+                }
+            }
+            CurveKind::Epitrochoid => {
+                let r_sum = self.outer_radius + self.inner_radius;
+                let ratio = r_sum / self.inner_radius;
 
-        Coordinate {
-            x: r_diff * theta.cos() + self.pen_offset * (ratio * theta).cos(),
-            y: r_diff * theta.sin() - self.pen_offset * (ratio * theta).sin(),
+                Coordinate {
+                    x: r_sum * theta.cos() - self.pen_offset * (ratio * theta).cos(),
+                    y: r_sum * theta.sin() - self.pen_offset * (ratio * theta).sin(),
+                }
+            }
         }
     }
 
-    /// Computes all the points of the hypotrochoid curve and recenters them.
-    /// The result is an array of coordinates centered around the origin.
-    pub fn compute_points(&self) -> Vec<Coordinate> {
-        let mut points = Vec::with_capacity(self.steps + 1);
-        let (mut max_x, mut min_x) = (f64::MIN, f64::MAX);
-        let (mut max_y, mut min_y) = (f64::MIN, f64::MAX);
-
-        // The number of revolutions is hard-coded for simplicity's sake.
-        // It can be calculated from the large (R) and small (r) radius to "close" the curve.
-        // Formula: `revolutions = r / gcd(R, r)` (using integer radius).
-        const REVOLUTIONS: f64 = 16.0;
-        
-        // Computes raw points and updates the bounding box extents.
-        for j in 0..=self.steps {
-            let theta = 2.0 * PI * (j as f64) / (self.steps as f64) * REVOLUTIONS;
-            let p = self.generate_point(theta);
-            points.push(p);
-
-            max_x = max_x.max(p.x);
-            min_x = min_x.min(p.x);
-            max_y = max_y.max(p.y);
-            min_y = min_y.min(p.y);
-        }
-
-        // Computes the center offset based on the bounding box.
-        let offset_x = (max_x + min_x) / 2.0;
-        let offset_y = (max_y + min_y) / 2.0;
-
-        // Recenters all points by substracting the center offset.
-        for p in &mut points {
-            p.x -= offset_x;
-            p.y -= offset_y;
+    /// Derives the parametric equation of the hypotrochoid term by term.
+    fn tangent(&self, theta: f64) -> Coordinate {
+        match self.kind {
+            CurveKind::Hypotrochoid => {
+                let r_diff = self.outer_radius - self.inner_radius;
+                let ratio = r_diff / self.inner_radius;
+
+                Coordinate {
+                    x: -r_diff * theta.sin() - self.pen_offset * ratio * (ratio * theta).sin(),
+                    y: r_diff * theta.cos() - self.pen_offset * ratio * (ratio * theta).cos(),
+                }
+            }
+            CurveKind::Epitrochoid => {
+                let r_sum = self.outer_radius + self.inner_radius;
+                let ratio = r_sum / self.inner_radius;
+
+                Coordinate {
+                    x: -r_sum * theta.sin() + self.pen_offset * ratio * (ratio * theta).sin(),
+                    y: r_sum * theta.cos() - self.pen_offset * ratio * (ratio * theta).cos(),
+                }
+            }
         }
+    }
 
-        points
+    fn steps(&self) -> usize {
+        self.steps
+    }
+
+    /// The number of revolutions is calculated from the large (R) and small (r) radius to "close" the curve.
+    /// Formula: `revolutions = r / gcd(R, r)` (see `required_revolutions`).
+    /// Curves that never close, or would take too long to, are drawn over `FALLBACK_REVOLUTIONS`.
+    fn revolutions(&self) -> f64 {
+        self.required_revolutions().unwrap_or(FALLBACK_REVOLUTIONS) as f64
+    }
+
+    #[cfg(feature = "rayon")]
+    fn compute_points(&self) -> Vec<Coordinate> {
+        par_sample_points(self)
+    }
+
+    #[cfg(feature = "rayon")]
+    fn bounding_box(&self) -> BoundingBox {
+        par_bounding_box(self)
+    }
+}
+
+impl Default for Hypotrochoid {
+    fn default() -> Self {
+        Hypotrochoid::checked(150.0, 52.5, 97.5, 3000, CurveKind::Hypotrochoid).expect("the default curve is valid")
+    }
+}
+
+impl Hypotrochoid {
+    /// Builds a curve, rejecting parameters describing a degenerate shape: both radii
+    /// must be positive, the pen offset must be finite, and the curve needs at least
+    /// one step. A hypotrochoid also needs radii differing from each other, or its
+    /// rolling circle would spin in place; an epitrochoid may have equal radii.
+    /// Curves built this way never produce non-finite points.
+    #[cfg(feature = "std")]
+    pub fn new(outer_radius: f64, inner_radius: f64, pen_offset: f64, steps: usize, kind: CurveKind) -> Result<Self, RosettaError> {
+        Hypotrochoid::checked(outer_radius, inner_radius, pen_offset, steps, kind).map_err(RosettaError::InvalidCurve)
+    }
+
+    /// Builds a curve after the checks of `new`, describing what's wrong on failure.
+    /// Unlike `new`, this is available without `std`, for the presets.
+    fn checked(outer_radius: f64, inner_radius: f64, pen_offset: f64, steps: usize, kind: CurveKind) -> Result<Self, String> {
+        if !(outer_radius.is_finite() && outer_radius > 0.0) {
+            return Err(format!("outer_radius must be > 0, got {}", outer_radius));
+        }
+        if !(inner_radius.is_finite() && inner_radius > 0.0) {
+            return Err(format!("inner_radius must be > 0, got {}", inner_radius));
+        }
+        if kind == CurveKind::Hypotrochoid && inner_radius == outer_radius {
+            return Err(format!("inner_radius must differ from outer_radius, both are {}", outer_radius));
+        }
+        if !pen_offset.is_finite() {
+            return Err(format!("pen_offset must be finite, got {}", pen_offset));
+        }
+        if steps == 0 {
+            return Err(String::from("steps must be >= 1, got 0"));
+        }
+        Ok(Hypotrochoid { outer_radius, inner_radius, pen_offset, steps, kind })
+    }
+
+    /// Builds a curve from its outer radius and the two other lengths expressed as
+    /// fractions of it: `inner_radius = outer_radius * inner_ratio` and
+    /// `pen_offset = outer_radius * pen_ratio`. Scaling such a design only takes
+    /// changing `outer_radius`, the shape being preserved.
+    /// The inner ratio must lie in (0, 1) and the pen ratio in [0, 1], and the
+    /// resulting curve must pass the checks of `new`.
+    #[cfg(feature = "std")]
+    pub fn from_ratios(outer_radius: f64, inner_ratio: f64, pen_ratio: f64, steps: usize) -> Result<Self, RosettaError> {
+        if !(inner_ratio > 0.0 && inner_ratio < 1.0) {
+            return Err(RosettaError::InvalidCurve(format!("inner_ratio must be in (0, 1), got {}", inner_ratio)));
+        }
+        if !(0.0..=1.0).contains(&pen_ratio) {
+            return Err(RosettaError::InvalidCurve(format!("pen_ratio must be in [0, 1], got {}", pen_ratio)));
+        }
+        Hypotrochoid::new(outer_radius, outer_radius * inner_ratio, outer_radius * pen_ratio, steps, CurveKind::Hypotrochoid)
+    }
+
+    /// Returns a copy of the curve with another outer radius.
+    ///
+    /// ```
+    /// use rustigraph::rosetta::Hypotrochoid;
+    ///
+    /// let curve = Hypotrochoid::default().with_outer_radius(175.0);
+    /// assert_eq!(curve.outer_radius, 175.0);
+    /// assert_eq!(curve.inner_radius, Hypotrochoid::default().inner_radius);
+    /// ```
+    pub fn with_outer_radius(self, outer_radius: f64) -> Self {
+        Hypotrochoid { outer_radius, ..self }
+    }
+
+    /// Returns a copy of the curve with another inner radius.
+    ///
+    /// ```
+    /// use rustigraph::rosetta::Hypotrochoid;
+    ///
+    /// // Rolling a circle a fifth of the fixed one draws five petals.
+    /// let curve = Hypotrochoid::default().with_inner_radius(30.0);
+    /// assert_eq!(curve.petal_count(), Some(5));
+    /// ```
+    pub fn with_inner_radius(self, inner_radius: f64) -> Self {
+        Hypotrochoid { inner_radius, ..self }
+    }
+
+    /// Returns a copy of the curve with another pen offset.
+    ///
+    /// ```
+    /// use rustigraph::rosetta::Hypotrochoid;
+    ///
+    /// // A pen inside the rolling circle traces no loops.
+    /// let curve = Hypotrochoid::default().with_pen_offset(40.0);
+    /// assert!(!curve.has_inner_loops());
+    /// ```
+    pub fn with_pen_offset(self, pen_offset: f64) -> Self {
+        Hypotrochoid { pen_offset, ..self }
+    }
+
+    /// Returns a copy of the curve rolling its circle on another side of the fixed one.
+    ///
+    /// ```
+    /// use rustigraph::rosetta::{CurveKind, Hypotrochoid};
+    ///
+    /// let curve = Hypotrochoid::default().with_kind(CurveKind::Epitrochoid);
+    /// assert_eq!(curve.kind, CurveKind::Epitrochoid);
+    /// ```
+    pub fn with_kind(self, kind: CurveKind) -> Self {
+        Hypotrochoid { kind, ..self }
+    }
+
+    /// Returns a copy of the curve sampled with another number of steps.
+    ///
+    /// ```
+    /// use rustigraph::rosetta::{Curve, Hypotrochoid};
+    ///
+    /// let curve = Hypotrochoid::default().with_steps(500);
+    /// assert_eq!(curve.compute_points().len(), 501);
+    /// ```
+    pub fn with_steps(self, steps: usize) -> Self {
+        Hypotrochoid { steps, ..self }
+    }
+
+    /// Decomposes the curve into its exact Fourier terms, as `(frequency, coefficient)` pairs,
+    /// such that the point at angle theta is `sum(coefficient * exp(i * frequency * theta))`.
+    /// A hypotrochoid has two terms: the center of the rolling circle turning once per
+    /// revolution, and the pen turning backwards around it; an `Epicyclic` curve with
+    /// these frequencies as rates and amplitudes as radii traces the same points.
+    /// The pen of an epitrochoid turns forwards instead, its term being subtracted.
+    /// The terms describe the raw points, before `compute_points` recenters them.
+    pub fn fourier_coefficients(&self) -> Vec<(f64, Complex)> {
+        match self.kind {
+            CurveKind::Hypotrochoid => {
+                let r_diff = self.outer_radius - self.inner_radius;
+                let ratio = r_diff / self.inner_radius;
+                vec![
+                    (1.0, Complex { re: r_diff, im: 0.0 }),
+                    (-ratio, Complex { re: self.pen_offset, im: 0.0 }),
+                ]
+            }
+            CurveKind::Epitrochoid => {
+                let r_sum = self.outer_radius + self.inner_radius;
+                let ratio = r_sum / self.inner_radius;
+                vec![
+                    (1.0, Complex { re: r_sum, im: 0.0 }),
+                    (ratio, Complex { re: -self.pen_offset, im: 0.0 }),
+                ]
+            }
+        }
+    }
+
+    /// Computes the number of revolutions needed to close the curve.
+    /// Both radii are scaled to integers, then `revolutions = r / gcd(R, r)`.
+    /// Returns `None` when the curve doesn't close within `MAX_REVOLUTIONS`.
+    pub fn required_revolutions(&self) -> Option<u64> {
+        let (outer, inner) = self.integer_radii()?;
+        let revolutions = inner / gcd(outer, inner);
+        (revolutions <= MAX_REVOLUTIONS).then_some(revolutions)
+    }
+
+    /// Checks whether the pen traces loops, moving backwards for a while on each of them.
+    /// That's the case exactly when the pen lies outside the rolling circle
+    /// (`pen_offset > inner_radius`); on the circle, the loops shrink to cusps.
+    /// A hypotrochoid rolling a circle as large as the fixed one stays still, without loops.
+    pub fn has_inner_loops(&self) -> bool {
+        let still = self.kind == CurveKind::Hypotrochoid && self.outer_radius == self.inner_radius;
+        self.pen_offset.abs() > self.inner_radius && !still
+    }
+
+    /// Counts the loops of the curve, zero when it has none.
+    /// The pen loops once each time the rolling circle rolls its own circumference,
+    /// so a closed curve has `R / gcd(R, r)` loops; one that doesn't close gets a loop
+    /// for every full circumference rolled over the sampled revolutions.
+    /// This isn't the number of crossings `geometry::self_intersections` finds on the sampled
+    /// points: overlapping petals cross each other too, with or without loops.
+    pub fn inner_loop_count(&self) -> usize {
+        if !self.has_inner_loops() {
+            return 0;
+        }
+        match self.petal_count() {
+            Some(petals) => petals as usize,
+            None => (self.revolutions() * self.outer_radius / self.inner_radius).floor() as usize,
+        }
+    }
+
+    /// Counts the petals of a closed curve, i.e. the lobes it traces around its center:
+    /// `R / gcd(R, r)`, looping or not depending on the pen offset.
+    /// Returns `None` when the curve doesn't close within `MAX_REVOLUTIONS`.
+    pub fn petal_count(&self) -> Option<u64> {
+        self.required_revolutions()?;
+        let (outer, inner) = self.integer_radii()?;
+        Some(outer / gcd(outer, inner))
+    }
+
+    /// Scales both radii to the smallest integers, taking up to `RADIUS_PRECISION` decimals
+    /// into account. Returns `None` for radii with more decimals, or zero.
+    fn integer_radii(&self) -> Option<(u64, u64)> {
+        for digits in 0..=RADIUS_PRECISION {
+            let scale = 10f64.powi(digits);
+            let outer = (self.outer_radius * scale).abs();
+            let inner = (self.inner_radius * scale).abs();
+            if (outer - outer.round()).abs() > 1e-6 || (inner - inner.round()).abs() > 1e-6 {
+                continue;
+            }
+
+            let (outer, inner) = (outer.round() as u64, inner.round() as u64);
+            return (outer != 0 && inner != 0).then_some((outer, inner));
+        }
+        None
+    }
+
+    /// Computes statistics describing the sampled curve.
+    /// Warns when the sampled curve doesn't close within `CLOSE_TOLERANCE`.
+    pub fn stats(&self) -> CurveStats {
+        let points = self.compute_points();
+        let bounds = BoundingBox::from_points(&points);
+        let closure_error = match (points.first(), points.last()) {
+            (Some(first), Some(last)) => (last.x - first.x).hypot(last.y - first.y),
+            _ => 0.0,
+        };
+        if closure_error > CLOSE_TOLERANCE {
+            warn!("curve ends {} units away from its start; adjust its steps or revolutions to close it", closure_error);
+        }
+        CurveStats {
+            point_count: self.point_count(),
+            width: bounds.width(),
+            height: bounds.height(),
+            required_revolutions: self.required_revolutions(),
+            closure_error,
+            inner_loops: self.inner_loop_count(),
+        }
+    }
+}
+
+/// Radius of the rolling circle of the curves built by `fit_parameters_to_petals`.
+const PETAL_INNER_RADIUS: f64 = 25.0;
+
+/// Builds a flower with the given number of petals, closing after a single revolution.
+/// The outer radius is `petals` times the inner one, both integers, so the curve has
+/// exactly `petals` rounded lobes; the pen sits inside the rolling circle, keeping
+/// them free of loops. Six petals give the default outer radius, and the curve grows
+/// with the count: `scale_to_fit` brings it back to a given size. A flower needs at
+/// least two petals, so smaller counts are raised to two.
+pub fn fit_parameters_to_petals(petals: u32) -> Hypotrochoid {
+    let petals = petals.max(2);
+    let steps = Hypotrochoid::default().steps;
+    Hypotrochoid::checked(petals as f64 * PETAL_INNER_RADIUS, PETAL_INNER_RADIUS, 0.8 * PETAL_INNER_RADIUS, steps, CurveKind::Hypotrochoid)
+        .expect("at least two petals keep the radii apart")
+}
+
+/// Computes the greatest common divisor of two integers.
+pub(crate) fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+// Statistics about a sampled curve.
+#[derive(Debug, Clone, Copy)]
+pub struct CurveStats {
+    pub point_count: usize,                  // Number of points on the path.
+    pub width: f64,                          // Horizontal extent of the curve.
+    pub height: f64,                         // Vertical extent of the curve.
+    pub required_revolutions: Option<u64>,   // Revolutions needed to close, if it ever does.
+    pub closure_error: f64,                  // Gap between the last sampled point and the first one.
+    pub inner_loops: usize,                  // Number of loops traced by the pen, zero for none.
+}
+
+impl fmt::Display for CurveStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} points, {:.1} x {:.1}, ", self.point_count, self.width, self.height)?;
+        match self.required_revolutions {
+            Some(revolutions) => write!(f, "closes after {} revolutions", revolutions),
+            None => write!(f, "does not close (capped at {} revolutions)", MAX_REVOLUTIONS),
+        }?;
+        write!(f, ", closure error {:.3}, {} inner loops", self.closure_error, self.inner_loops)
+    }
+}
+
+// A spirograph made of several stacked gears, each one modeled as a vector of
+// fixed length rotating at its own rate. The pen sits at the tip of the chain,
+// so its position is the sum of all the rotating vectors (a truncated Fourier series).
+#[derive(Debug, Clone)]
+pub struct Epicyclic {
+    pub radii: Vec<f64>, // Length of each rotating vector, from the center outwards.
+    pub rates: Vec<f64>, // Angular speed of each vector, in turns per revolution.
+    pub steps: usize,    // Number of steps (points) used to approximate the curve.
+}
+
+impl Curve for Epicyclic {
+    /// Computes the pen position by summing every rotating vector at angle theta.
+    /// Extra radii or rates without a counterpart are ignored.
+    fn generate_point(&self, theta: f64) -> Coordinate {
+        self.radii.iter().zip(&self.rates).fold(
+            Coordinate { x: 0.0, y: 0.0 },
+            |acc, (radius, rate)| Coordinate {
+                x: acc.x + radius * (rate * theta).cos(),
+                y: acc.y + radius * (rate * theta).sin(),
+            },
+        )
+    }
+
+    /// Sums the derivative of every rotating vector at angle theta.
+    fn tangent(&self, theta: f64) -> Coordinate {
+        self.radii.iter().zip(&self.rates).fold(
+            Coordinate { x: 0.0, y: 0.0 },
+            |acc, (radius, rate)| Coordinate {
+                x: acc.x - radius * rate * (rate * theta).sin(),
+                y: acc.y + radius * rate * (rate * theta).cos(),
+            },
+        )
+    }
+
+    fn steps(&self) -> usize {
+        self.steps
+    }
+
+    /// With integer rates every vector is back to its start after a single turn,
+    /// which closes the curve.
+    fn revolutions(&self) -> f64 {
+        1.0
+    }
+
+    #[cfg(feature = "rayon")]
+    fn compute_points(&self) -> Vec<Coordinate> {
+        par_sample_points(self)
+    }
+
+    #[cfg(feature = "rayon")]
+    fn bounding_box(&self) -> BoundingBox {
+        par_bounding_box(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn new_rejects_degenerate_curves() {
+        let zero_inner = Hypotrochoid::new(150.0, 0.0, 50.0, 100, CurveKind::Hypotrochoid);
+        assert!(matches!(zero_inner, Err(RosettaError::InvalidCurve(_))));
+        let equal_radii = Hypotrochoid::new(150.0, 150.0, 50.0, 100, CurveKind::Hypotrochoid);
+        assert!(matches!(equal_radii, Err(RosettaError::InvalidCurve(_))));
+        let no_steps = Hypotrochoid::new(150.0, 50.0, 50.0, 0, CurveKind::Hypotrochoid);
+        assert!(matches!(no_steps, Err(RosettaError::InvalidCurve(_))));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn new_accepts_epitrochoids_with_equal_radii() {
+        let curve = Hypotrochoid::new(100.0, 100.0, 50.0, 100, CurveKind::Epitrochoid).unwrap();
+        assert_eq!(curve.kind, CurveKind::Epitrochoid);
+        assert!(curve.compute_points().iter().all(|p| p.x.is_finite() && p.y.is_finite()));
+    }
+
+    /// Reads back the points of SVG path data, control points included, in order.
+    fn path_points(data: &str) -> Vec<Coordinate> {
+        data.split_whitespace()
+            .filter_map(|token| token.split_once(','))
+            .map(|(x, y)| Coordinate { x: x.parse().unwrap(), y: y.parse().unwrap() })
+            .collect()
+    }
+
+    #[test]
+    fn smooth_paths_need_fewer_points() {
+        let curve = Hypotrochoid::default();
+        let smooth = format_smooth_path_data(&curve.with_steps(300).compute_points(), Some(2));
+        let lines = format_path_data(&curve.with_steps(3000).compute_points(), Some(2), false);
+        assert!(smooth.len() < lines.len(), "{} bytes of curves against {} bytes of lines", smooth.len(), lines.len());
+    }
+
+    #[test]
+    fn smooth_paths_dont_kink_at_the_seam() {
+        let points = Hypotrochoid::default().with_steps(60).compute_points();
+        let controls = path_points(&format_smooth_path_data(&points, None));
+        let n = controls.len();
+        let (first, first_control) = (controls[0], controls[1]);
+        let (last, last_control) = (controls[n - 1], controls[n - 2]);
+        let leaving = Coordinate { x: first_control.x - first.x, y: first_control.y - first.y };
+        let arriving = Coordinate { x: last.x - last_control.x, y: last.y - last_control.y };
+        assert!((leaving.x - arriving.x).abs() < 1e-9 && (leaving.y - arriving.y).abs() < 1e-9, "{:?} against {:?}", leaving, arriving);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn parallel_sampling_matches_sequential_sampling() {
+        let curve = Hypotrochoid::default().with_steps(PARALLEL_MIN_STEPS * 3 + 7);
+        let parallel = par_sample_points(&curve);
+        let sequential = sample_points(&curve);
+        assert_eq!(parallel.len(), sequential.len());
+        assert!(parallel.iter().zip(&sequential).all(|(p, q)| p.x.to_bits() == q.x.to_bits() && p.y.to_bits() == q.y.to_bits()));
+
+        let (parallel, sequential) = (par_bounding_box(&curve), curve.points_iter().collect::<BoundingBox>());
+        assert_eq!((parallel.min, parallel.max), (sequential.min, sequential.max));
+    }
+
+    #[test]
+    fn rolling_circles_larger_than_the_fixed_one_stay_finite() {
+        for (outer, inner, pen_offset) in [(100.0, 150.0, 60.0), (50.0, 175.0, 200.0), (30.0, 31.5, 0.0)] {
+            let curve = Hypotrochoid::default().with_outer_radius(outer).with_inner_radius(inner).with_pen_offset(pen_offset);
+            let points = curve.compute_points();
+            assert!(points.iter().all(|p| p.x.is_finite() && p.y.is_finite()), "R = {}, r = {}", outer, inner);
+            let center = BoundingBox::from_points(&points).center();
+            assert!(center.x.abs() < 1e-9 && center.y.abs() < 1e-9, "R = {}, r = {} is centered on {:?}", outer, inner, center);
+        }
+    }
+
+    #[test]
+    fn point_count_matches_the_sampled_points() {
+        let epicyclic = Epicyclic { radii: vec![100.0, 40.0, 10.0], rates: vec![1.0, -4.0, 9.0], steps: 250 };
+        assert_eq!(epicyclic.compute_points().len(), epicyclic.point_count());
+        for curve in [Hypotrochoid::default(), fit_parameters_to_petals(5).with_steps(1), Hypotrochoid::default().with_kind(CurveKind::Epitrochoid).with_steps(777)] {
+            assert_eq!(curve.compute_points().len(), curve.point_count());
+            assert_eq!(curve.points_iter().len(), curve.point_count());
+        }
+    }
+
+    #[test]
+    fn scaled_curves_fit_their_area() {
+        let curves = [Hypotrochoid::default(), fit_parameters_to_petals(7), Hypotrochoid::default().with_kind(CurveKind::Epitrochoid).with_steps(500)];
+        for curve in curves {
+            for (width, height, margin) in [(800.0, 600.0, 20.0), (300.0, 900.0, 0.0), (100.0, 100.0, 15.0)] {
+                let scale = curve.scale_to_fit(width, height, margin);
+                let scaled: Vec<Coordinate> = curve.compute_points().iter().map(|p| Coordinate { x: p.x * scale, y: p.y * scale }).collect();
+                let bounds = BoundingBox::from_points(&scaled);
+                let (room_x, room_y) = (width - 2.0 * margin, height - 2.0 * margin);
+                assert!(bounds.width() <= room_x + 1e-9 && bounds.height() <= room_y + 1e-9, "{:?} overflows {} x {}", bounds, room_x, room_y);
+
+                // The curve touches the area along one of its sides at least.
+                let slack = (room_x - bounds.width()).min(room_y - bounds.height());
+                assert!(slack.abs() < 1e-9, "{} units left around {:?}", slack, bounds);
+            }
+        }
+    }
+
+    #[test]
+    fn speed_profile_is_normalized() {
+        for curve in [Hypotrochoid::default(), Hypotrochoid::default().with_kind(CurveKind::Epitrochoid).with_steps(500)] {
+            let speeds = curve.speed_profile();
+            assert_eq!(speeds.len(), curve.point_count());
+            assert!(speeds.iter().all(|speed| (0.0..=1.0).contains(speed)));
+            assert_eq!(speeds.iter().copied().fold(0.0, f64::max), 1.0);
+        }
+    }
+
+    #[test]
+    fn fourier_coefficients_rebuild_the_curve() {
+        for kind in [CurveKind::Hypotrochoid, CurveKind::Epitrochoid] {
+            let curve = Hypotrochoid::default().with_kind(kind);
+            let terms = curve.fourier_coefficients();
+            for j in 0..100 {
+                let theta = j as f64 * 0.37;
+                // Sums `coefficient * exp(i * frequency * theta)` over the terms.
+                let (x, y) = terms.iter().fold((0.0, 0.0), |(x, y), (frequency, c)| {
+                    let (sin, cos) = (frequency * theta).sin_cos();
+                    (x + c.re * cos - c.im * sin, y + c.re * sin + c.im * cos)
+                });
+                let expected = curve.generate_point(theta);
+                assert!((x - expected.x).abs() < 1e-9 && (y - expected.y).abs() < 1e-9, "{:?} at {} for {:?}", expected, theta, kind);
+            }
+
+            let epicyclic = Epicyclic { radii: terms.iter().map(|(_, c)| c.re).collect(), rates: terms.iter().map(|(f, _)| *f).collect(), steps: 10 };
+            let expected = curve.generate_point(1.0);
+            let rebuilt = epicyclic.generate_point(1.0);
+            assert!((rebuilt.x - expected.x).abs() < 1e-9 && (rebuilt.y - expected.y).abs() < 1e-9);
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn coordinates_serialize_as_pairs() {
+        let point = Coordinate { x: 1.0, y: 2.0 };
+        let text = serde_json::to_string(&point).unwrap();
+        assert_eq!(text, "[1.0,2.0]");
+        assert_eq!(serde_json::from_str::<Coordinate>(&text).unwrap(), point);
+        assert!(serde_json::from_str::<Coordinate>("[1.0]").is_err());
+    }
+
+    #[test]
+    fn revolutions_close_the_curve_or_fall_back() {
+        let with_radii = |outer: f64, inner: f64| Hypotrochoid::default().with_outer_radius(outer).with_inner_radius(inner);
+        assert_eq!(with_radii(150.0, 52.5).required_revolutions(), Some(7));
+        assert_eq!(with_radii(150.0, 50.0).required_revolutions(), Some(1));
+        assert_eq!(with_radii(100.0, 0.999).required_revolutions(), Some(999));
+        assert_eq!(with_radii(150.0, 52.5).revolutions(), 7.0);
+
+        // 1.001 only closes after 1001 revolutions, and 52.1234 has more decimals than are looked at.
+        for curve in [with_radii(100.0, 1.001), with_radii(150.0, 52.1234)] {
+            assert_eq!(curve.required_revolutions(), None);
+            assert_eq!(curve.revolutions(), FALLBACK_REVOLUTIONS as f64);
+        }
+    }
+
+    #[test]
+    fn segments_end_on_their_angles() {
+        let curve = Hypotrochoid::default();
+        let (theta_a, theta_b) = (0.3, 2.0 * PI * 4.7);
+        for n in [2, 3, 100, 1001] {
+            let points = curve.sample_segment(theta_a, theta_b, n);
+            assert_eq!(points.len(), n);
+            assert_eq!(points[0], curve.generate_point(theta_a));
+            assert_eq!(points[n - 1], curve.generate_point(theta_b));
+        }
+        assert_eq!(curve.sample_segment(theta_a, theta_b, 1), vec![curve.generate_point(theta_a)]);
+        assert!(curve.sample_segment(theta_a, theta_b, 0).is_empty());
+    }
+
+    #[test]
+    fn closure_error_tells_closed_curves_from_open_ones() {
+        for curve in [Hypotrochoid::default(), fit_parameters_to_petals(7), Hypotrochoid::default().with_kind(CurveKind::Epitrochoid)] {
+            assert!(curve.stats().closure_error < CLOSE_TOLERANCE);
+        }
+
+        // Radii with too many decimals don't close, and are cut after `FALLBACK_REVOLUTIONS`.
+        let truncated = Hypotrochoid::default().with_inner_radius(52.1234);
+        assert_eq!(truncated.stats().required_revolutions, None);
+        assert!(truncated.stats().closure_error > 1.0, "{}", truncated.stats().closure_error);
+    }
+
+    #[test]
+    fn loops_appear_once_the_pen_leaves_the_rolling_circle() {
+        // Inside the rolling circle, and on it where the loops shrink to cusps.
+        for pen_offset in [40.0, 52.5] {
+            let curve = Hypotrochoid::default().with_pen_offset(pen_offset);
+            assert!(!curve.has_inner_loops(), "d = {}", pen_offset);
+            assert_eq!(curve.inner_loop_count(), 0);
+        }
+
+        // Outside of it, one loop per petal: R / gcd(R, r) = 1500 / 75.
+        let curve = Hypotrochoid::default();
+        assert!(curve.has_inner_loops());
+        assert_eq!(curve.inner_loop_count(), 20);
+        assert_eq!(curve.stats().inner_loops, 20);
+
+        // An open curve loops once per circumference rolled: 16 * 150 / 52.1234 times.
+        let open = Hypotrochoid::default().with_inner_radius(52.1234);
+        assert_eq!(open.petal_count(), None);
+        assert_eq!(open.inner_loop_count(), 46);
+
+        let still = Hypotrochoid { inner_radius: 150.0, ..Hypotrochoid::default() };
+        assert!(!still.has_inner_loops());
+    }
+
+    /// Counts the petals of a closed curve on its points, as the local maxima of their
+    /// distance to the center.
+    fn counted_petals(points: &[Coordinate]) -> usize {
+        // The last point comes back onto the first, so it's left out of the loop.
+        let radii: Vec<f64> = points[..points.len() - 1].iter().map(|p| p.x.hypot(p.y)).collect();
+        let n = radii.len();
+        (0..n).filter(|&i| radii[i] > radii[(i + n - 1) % n] && radii[i] >= radii[(i + 1) % n]).count()
+    }
+
+    #[test]
+    fn fitted_flowers_have_the_requested_petals() {
+        for petals in 2..=12 {
+            let curve = fit_parameters_to_petals(petals);
+            assert_eq!(counted_petals(&curve.compute_points()), petals as usize, "asked for {} petals", petals);
+            assert_eq!(curve.petal_count(), Some(petals as u64));
+            assert!(!curve.has_inner_loops());
+        }
+        assert_eq!(counted_petals(&fit_parameters_to_petals(0).compute_points()), 2);
+    }
+
+    #[test]
+    fn adaptive_sampling_bounds_its_size_and_chord_error() {
+        let curve = Hypotrochoid::default();
+        let revolutions = curve.revolutions();
+        let finest = (revolutions as usize * ADAPTIVE_SEEDS_PER_REVOLUTION) << MAX_SUBDIVISION_DEPTH;
+        let mut previous_count = 0;
+        for tolerance in [2.0, 0.5, 0.05] {
+            let points = curve.adaptive_sample(tolerance);
+            assert!(points.len() <= finest + 1);
+            assert!(points.len() > previous_count, "a lower tolerance needs more points");
+            previous_count = points.len();
+
+            // Every point lies on the grid of the finest subdivisions, where it's found again
+            // on the raw curve, which gives the angles of the chords.
+            let start = curve.generate_point(0.0);
+            let offset = Coordinate { x: start.x - points[0].x, y: start.y - points[0].y };
+            let raw_point = |k: usize| {
+                let q = curve.generate_point(sample_angle(k, finest, revolutions));
+                Coordinate { x: q.x - offset.x, y: q.y - offset.y }
+            };
+            let mut k = 0;
+            let mut angles = Vec::new();
+            for p in &points {
+                while (raw_point(k).x - p.x).hypot(raw_point(k).y - p.y) > 1e-6 {
+                    k += 1;
+                }
+                angles.push(sample_angle(k, finest, revolutions));
+            }
+            assert_eq!(k, finest);
+
+            // The curve can stray a bit further between the probes of the subdivision.
+            for (chord, angle) in points.windows(2).zip(angles.windows(2)) {
+                for t in 1..8 {
+                    let q = curve.generate_point(angle[0] + (angle[1] - angle[0]) * t as f64 / 8.0);
+                    let q = Coordinate { x: q.x - offset.x, y: q.y - offset.y };
+                    let error = distance_to_segment(q, chord[0], chord[1]);
+                    assert!(error <= 1.25 * tolerance, "chord error of {} for a tolerance of {}", error, tolerance);
+                }
+            }
+        }
     }
 }
@@ -0,0 +1,1133 @@
+use crate::color::is_css_color;
+use crate::error::RosettaError;
+use crate::geometry::polyline_length;
+#[cfg(feature = "rand")]
+use crate::renderer::JitterStyle;
+use crate::renderer::{is_svg_transform, parse_css_time, placed_curve, Animation, Background, CheckerStyle, DotStyle, Geometry, GridStyle, Pen, PointTransform, RenderOptions, RosettaStyle, TextOnPath, TwinkleStyle, Units, CONTENT_SCALE};
+use crate::rosetta::{Coordinate, Curve, CurveKind, Hypotrochoid};
+use log::debug;
+use std::collections::HashMap;
+#[cfg(feature = "serde")]
+use std::path::Path;
+
+/// Holds named styles registered once and shared by many scenes.
+/// Once filled, a registry can be read from several threads at once (e.g. behind an `Arc`).
+#[derive(Debug, Clone, Default)]
+pub struct StyleRegistry {
+    styles: HashMap<String, RosettaStyle>,
+}
+
+impl StyleRegistry {
+    /// Registers a style under the given name, replacing any previous one.
+    pub fn register(&mut self, name: impl Into<String>, style: RosettaStyle) {
+        self.styles.insert(name.into(), style);
+    }
+
+    /// Returns the style registered under the given name.
+    pub fn get(&self, name: &str) -> Result<&RosettaStyle, RosettaError> {
+        self.styles
+            .get(name)
+            .ok_or_else(|| RosettaError::UnknownStyle(name.to_string()))
+    }
+}
+
+/// Refers to the style of a curve, either directly or by its name in a registry.
+/// In scene files, a style is either a table of fields or the name of a registered style.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(untagged))]
+// Most curves hold their style inline, so boxing it wouldn't save any memory.
+#[allow(clippy::large_enum_variant)]
+pub enum StyleRef {
+    Inline(RosettaStyle), // The style itself.
+    Named(String),        // The name of a registered style, resolved at render time.
+}
+
+impl StyleRef {
+    /// Returns the style, looking it up in the registry when it's named.
+    pub fn resolve<'a>(&'a self, registry: &'a StyleRegistry) -> Result<&'a RosettaStyle, RosettaError> {
+        match self {
+            StyleRef::Inline(style) => Ok(style),
+            StyleRef::Named(name) => registry.get(name),
+        }
+    }
+}
+
+impl From<RosettaStyle> for StyleRef {
+    fn from(style: RosettaStyle) -> Self {
+        StyleRef::Inline(style)
+    }
+}
+
+/// Defines a rosetta placed on a scene.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SceneCurve {
+    pub style: StyleRef,                  // Geometry and appearance of the curve.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub offset: Coordinate,               // Position of the curve center, relative to the scene center.
+    #[cfg_attr(feature = "serde", serde(default = "unit_scale"))]
+    pub scale: f64,                       // Uniform scale factor applied to the curve points.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub label: Option<String>,            // Caption written below the curve.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub text_on_path: Option<TextOnPath>, // Text written along the curve, turning with it.
+}
+
+/// Returns the scale of curves whose scene file doesn't give one.
+#[cfg(feature = "serde")]
+fn unit_scale() -> f64 {
+    1.0
+}
+
+impl From<RosettaStyle> for SceneCurve {
+    /// Places the curve at the center of the scene, unscaled and without caption.
+    fn from(style: RosettaStyle) -> Self {
+        SceneCurve {
+            style: StyleRef::Inline(style),
+            offset: Coordinate { x: 0.0, y: 0.0 },
+            scale: 1.0,
+            label: None,
+            text_on_path: None,
+        }
+    }
+}
+
+/// Defines a complete composition: the document options and the curves drawn on it.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Scene {
+    pub options: RenderOptions,  // Document-level options.
+    pub curves: Vec<SceneCurve>, // Curves drawn in order, the last one on top.
+}
+
+impl Scene {
+    /// Starts building a scene with the default options and no curves.
+    ///
+    /// ```
+    /// use rustigraph::renderer::{default_styles, write_scene};
+    /// use rustigraph::scene::{Scene, StyleRegistry};
+    ///
+    /// // The scene drawn by the binary: the three predefined rosettas, on the default canvas.
+    /// let scene = default_styles().into_iter().fold(Scene::builder(), |builder, style| builder.add_curve(style)).build();
+    /// assert_eq!(scene.curves.len(), 3);
+    ///
+    /// let mut svg = Vec::new();
+    /// write_scene(&mut svg, &scene, &StyleRegistry::default())?;
+    /// assert!(String::from_utf8(svg)?.contains(r#"<path fill="none""#));
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn builder() -> SceneBuilder {
+        SceneBuilder::default()
+    }
+
+    /// Computes a hash of every parameter of the scene, to key a cache of rendered documents.
+    /// It's the same from one run, toolchain or platform to the next, unlike hashers seeded
+    /// with `RandomState`: every field is fed in a fixed order, floats as their raw bits, so
+    /// that any change of value, even `0.0` into `-0.0`, changes the hash. Named styles are
+    /// hashed by name, so the cache must also be cleared whenever the registry changes. Point
+    /// transforms can't be inspected and all hash alike, so scenes using them shouldn't be cached.
+    pub fn content_hash(&self) -> u64 {
+        let mut hasher = Fnv1a(FNV_OFFSET_BASIS);
+        self.feed(&mut hasher);
+        hasher.0
+    }
+
+    /// Checks that every value of the scene can be rendered.
+    /// The error names the offending field, e.g. `curves[1].style.geometry.inner_radius must be > 0`.
+    /// Named styles are only checked once resolved, at render time.
+    pub fn validate(&self) -> Result<(), RosettaError> {
+        validate_options(&self.options)?;
+        for (i, curve) in self.curves.iter().enumerate() {
+            let path = format!("curves[{}]", i);
+            check(curve.offset.x.is_finite() && curve.offset.y.is_finite(), &path, "offset", "must be finite")?;
+            check(curve.scale.is_finite() && curve.scale > 0.0, &path, "scale", "must be > 0")?;
+            if let Some(text) = &curve.text_on_path {
+                let text_path = format!("{}.text_on_path", path);
+                check(text.font_size.is_finite() && text.font_size > 0.0, &text_path, "font_size", "must be > 0")?;
+                check(is_css_color(&text.color), &text_path, "color", "must be a CSS color such as 'white' or '#fff'")?;
+                check(is_length(&text.start_offset), &text_path, "start_offset", "must be a length or a percentage such as '25%'")?;
+            }
+            if let StyleRef::Inline(style) = &curve.style {
+                validate_style(style, &format!("{}.style", path))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Assembles several scenes into one, drawing their curves in order, the last scene on top.
+    /// The canvas is as wide and as tall as the largest of them, and each curve keeps its
+    /// offset from the center. Every other option, background included, comes from the last
+    /// scene: it wins over the earlier ones. Element ids are assigned from the position of
+    /// the curves in the merged scene, so curves coming from different scenes never clash,
+    /// and chained curves pick up where the previous scene's chain ended. Named styles keep
+    /// their name and are all resolved against the same registry at render time.
+    pub fn merge(scenes: Vec<Scene>) -> Scene {
+        let Some(width) = scenes.iter().map(|scene| scene.options.width).max() else {
+            return Scene::default();
+        };
+        let height = scenes.iter().map(|scene| scene.options.height).max().unwrap_or_default();
+        let mut merged = Scene::default();
+        for scene in scenes {
+            merged.options = scene.options;
+            merged.curves.extend(scene.curves);
+        }
+        merged.options.width = width;
+        merged.options.height = height;
+        merged
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Scene {
+    /// Parses and validates a scene written in JSON.
+    pub fn from_json(text: &str) -> Result<Scene, RosettaError> {
+        let scene: Scene = serde_json::from_str(text).map_err(|e| RosettaError::Config(e.to_string()))?;
+        scene.validate()?;
+        Ok(scene)
+    }
+
+    /// Parses and validates a scene written in TOML.
+    pub fn from_toml(text: &str) -> Result<Scene, RosettaError> {
+        let scene: Scene = toml::from_str(text).map_err(|e| RosettaError::Config(e.to_string()))?;
+        scene.validate()?;
+        Ok(scene)
+    }
+
+    /// Loads a scene file, picking the format from its `.json` or `.toml` extension.
+    pub fn load(path: &Path) -> Result<Scene, RosettaError> {
+        let text = std::fs::read_to_string(path)?;
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("json") => Scene::from_json(&text),
+            Some("toml") => Scene::from_toml(&text),
+            _ => Err(RosettaError::Config(format!("unsupported scene file '{}', expected a .json or .toml file", path.display()))),
+        }
+    }
+
+    /// Writes the scene as pretty-printed JSON, which `from_json` reads back into the same scene.
+    /// Point transforms are code rather than data, so they're left out.
+    pub fn to_json(&self) -> Result<String, RosettaError> {
+        serde_json::to_string_pretty(self).map_err(|e| RosettaError::Config(e.to_string()))
+    }
+
+    /// Writes the scene as TOML, which `from_toml` reads back into the same scene.
+    /// Point transforms are code rather than data, so they're left out.
+    pub fn to_toml(&self) -> Result<String, RosettaError> {
+        toml::to_string(self).map_err(|e| RosettaError::Config(e.to_string()))
+    }
+
+    /// Saves the scene to a JSON file, e.g. to keep a generated scene and `load` it later.
+    pub fn save_json(&self, path: &Path) -> Result<(), RosettaError> {
+        std::fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+
+    /// Saves the scene to a TOML file, e.g. to keep a generated scene and `load` it later.
+    pub fn save_toml(&self, path: &Path) -> Result<(), RosettaError> {
+        std::fs::write(path, self.to_toml()?)?;
+        Ok(())
+    }
+}
+
+/// Starting state of the 64-bit FNV-1a hash.
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+
+/// Multiplier of the 64-bit FNV-1a hash.
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// Hashes bytes as they're written, with the 64-bit FNV-1a function.
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    /// Mixes the bytes into the hash, one after the other.
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ byte as u64).wrapping_mul(FNV_PRIME);
+        }
+    }
+}
+
+/// Feeds a value to the hash of `Scene::content_hash`, field after field.
+/// Integers are fed as little-endian `u64`, so the hash doesn't depend on the platform,
+/// and strings and lists are preceded by their length, so their ends can't be mistaken
+/// for the start of the next field. Enums are preceded by the position of their variant.
+trait ContentHash {
+    fn feed(&self, hasher: &mut Fnv1a);
+}
+
+impl ContentHash for u64 {
+    fn feed(&self, hasher: &mut Fnv1a) {
+        hasher.write(&self.to_le_bytes());
+    }
+}
+
+impl ContentHash for usize {
+    fn feed(&self, hasher: &mut Fnv1a) {
+        (*self as u64).feed(hasher);
+    }
+}
+
+impl ContentHash for u32 {
+    fn feed(&self, hasher: &mut Fnv1a) {
+        (*self as u64).feed(hasher);
+    }
+}
+
+impl ContentHash for bool {
+    fn feed(&self, hasher: &mut Fnv1a) {
+        (*self as u64).feed(hasher);
+    }
+}
+
+impl ContentHash for f64 {
+    fn feed(&self, hasher: &mut Fnv1a) {
+        self.to_bits().feed(hasher);
+    }
+}
+
+impl ContentHash for f32 {
+    fn feed(&self, hasher: &mut Fnv1a) {
+        (self.to_bits() as u64).feed(hasher);
+    }
+}
+
+impl ContentHash for String {
+    fn feed(&self, hasher: &mut Fnv1a) {
+        self.len().feed(hasher);
+        hasher.write(self.as_bytes());
+    }
+}
+
+impl<T: ContentHash> ContentHash for Option<T> {
+    fn feed(&self, hasher: &mut Fnv1a) {
+        match self {
+            None => 0u64.feed(hasher),
+            Some(value) => {
+                1u64.feed(hasher);
+                value.feed(hasher);
+            }
+        }
+    }
+}
+
+impl<T: ContentHash> ContentHash for Vec<T> {
+    fn feed(&self, hasher: &mut Fnv1a) {
+        self.len().feed(hasher);
+        self.iter().for_each(|value| value.feed(hasher));
+    }
+}
+
+impl<A: ContentHash, B: ContentHash> ContentHash for (A, B) {
+    fn feed(&self, hasher: &mut Fnv1a) {
+        self.0.feed(hasher);
+        self.1.feed(hasher);
+    }
+}
+
+impl ContentHash for Coordinate {
+    fn feed(&self, hasher: &mut Fnv1a) {
+        self.x.feed(hasher);
+        self.y.feed(hasher);
+    }
+}
+
+impl ContentHash for Scene {
+    fn feed(&self, hasher: &mut Fnv1a) {
+        let Scene { options, curves } = self;
+        options.feed(hasher);
+        curves.feed(hasher);
+    }
+}
+
+impl ContentHash for SceneCurve {
+    fn feed(&self, hasher: &mut Fnv1a) {
+        let SceneCurve { style, offset, scale, label, text_on_path } = self;
+        style.feed(hasher);
+        offset.feed(hasher);
+        scale.feed(hasher);
+        label.feed(hasher);
+        text_on_path.feed(hasher);
+    }
+}
+
+impl ContentHash for StyleRef {
+    fn feed(&self, hasher: &mut Fnv1a) {
+        match self {
+            StyleRef::Inline(style) => {
+                0u64.feed(hasher);
+                style.feed(hasher);
+            }
+            StyleRef::Named(name) => {
+                1u64.feed(hasher);
+                name.feed(hasher);
+            }
+        }
+    }
+}
+
+impl ContentHash for RosettaStyle {
+    fn feed(&self, hasher: &mut Fnv1a) {
+        let RosettaStyle { geometry, pen, animation } = self;
+        geometry.feed(hasher);
+        pen.feed(hasher);
+        animation.feed(hasher);
+    }
+}
+
+impl ContentHash for Geometry {
+    fn feed(&self, hasher: &mut Fnv1a) {
+        let Geometry { outer_radius, inner_radius, distance, steps, kind } = self;
+        outer_radius.feed(hasher);
+        inner_radius.feed(hasher);
+        distance.feed(hasher);
+        steps.feed(hasher);
+        (*kind as u64).feed(hasher);
+    }
+}
+
+impl ContentHash for Pen {
+    fn feed(&self, hasher: &mut Fnv1a) {
+        let Pen { color, width, opacity, dash, segments, gradient, smooth } = self;
+        color.feed(hasher);
+        width.feed(hasher);
+        opacity.feed(hasher);
+        dash.feed(hasher);
+        segments.feed(hasher);
+        gradient.feed(hasher);
+        smooth.feed(hasher);
+    }
+}
+
+impl ContentHash for Animation {
+    fn feed(&self, hasher: &mut Fnv1a) {
+        let Animation { duration, rotate_from_deg, rotate_to_deg, alternate, delay, phase, draw_erase, chain, draw_in, twinkle } = self;
+        duration.feed(hasher);
+        rotate_from_deg.feed(hasher);
+        rotate_to_deg.feed(hasher);
+        alternate.feed(hasher);
+        delay.feed(hasher);
+        phase.feed(hasher);
+        draw_erase.feed(hasher);
+        chain.feed(hasher);
+        draw_in.feed(hasher);
+        twinkle.feed(hasher);
+    }
+}
+
+impl ContentHash for TwinkleStyle {
+    fn feed(&self, hasher: &mut Fnv1a) {
+        let TwinkleStyle { min_opacity, duration, begin } = self;
+        min_opacity.feed(hasher);
+        duration.feed(hasher);
+        begin.feed(hasher);
+    }
+}
+
+impl ContentHash for TextOnPath {
+    fn feed(&self, hasher: &mut Fnv1a) {
+        let TextOnPath { text, font_family, font_size, color, start_offset } = self;
+        text.feed(hasher);
+        font_family.feed(hasher);
+        font_size.feed(hasher);
+        color.feed(hasher);
+        start_offset.feed(hasher);
+    }
+}
+
+impl ContentHash for RenderOptions {
+    fn feed(&self, hasher: &mut Fnv1a) {
+        let RenderOptions {
+            width,
+            height,
+            preserve_aspect_ratio,
+            background_color,
+            background,
+            minify,
+            min_contrast,
+            units,
+            transform,
+            quantize,
+            standalone,
+            margin,
+            mode,
+            animation_backend,
+            interactive,
+            segment_length,
+            #[cfg(feature = "rand")]
+            color_jitter,
+            transform_fn,
+        } = self;
+        width.feed(hasher);
+        height.feed(hasher);
+        preserve_aspect_ratio.feed(hasher);
+        background_color.feed(hasher);
+        background.feed(hasher);
+        minify.feed(hasher);
+        min_contrast.feed(hasher);
+        (*units as u64).feed(hasher);
+        transform.feed(hasher);
+        quantize.feed(hasher);
+        standalone.feed(hasher);
+        margin.feed(hasher);
+        (*mode as u64).feed(hasher);
+        (*animation_backend as u64).feed(hasher);
+        interactive.feed(hasher);
+        segment_length.feed(hasher);
+        // Scenes without jitter hash alike whether or not the `rand` feature is on.
+        #[cfg(feature = "rand")]
+        if let Some(jitter) = color_jitter {
+            jitter.feed(hasher);
+        }
+        // Only whether there's a transform counts, as its code can't be read.
+        transform_fn.is_some().feed(hasher);
+    }
+}
+
+impl ContentHash for Background {
+    fn feed(&self, hasher: &mut Fnv1a) {
+        match self {
+            Background::None => 0u64.feed(hasher),
+            Background::Solid(color) => {
+                1u64.feed(hasher);
+                color.feed(hasher);
+            }
+            Background::LineGrid(GridStyle { step, color, stroke_width, opacity }) => {
+                2u64.feed(hasher);
+                step.feed(hasher);
+                color.feed(hasher);
+                stroke_width.feed(hasher);
+                opacity.feed(hasher);
+            }
+            Background::DotGrid(DotStyle { step, radius, color, opacity }) => {
+                3u64.feed(hasher);
+                step.feed(hasher);
+                radius.feed(hasher);
+                color.feed(hasher);
+                opacity.feed(hasher);
+            }
+            Background::Checker(CheckerStyle { size, color, opacity }) => {
+                4u64.feed(hasher);
+                size.feed(hasher);
+                color.feed(hasher);
+                opacity.feed(hasher);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "rand")]
+impl ContentHash for JitterStyle {
+    fn feed(&self, hasher: &mut Fnv1a) {
+        let JitterStyle { seed, hue_range, opacity_range } = self;
+        seed.feed(hasher);
+        hue_range.feed(hasher);
+        opacity_range.feed(hasher);
+    }
+}
+
+/// Fails with an error naming `path.field` unless `condition` holds.
+fn check(condition: bool, path: &str, field: &str, reason: &str) -> Result<(), RosettaError> {
+    if condition {
+        Ok(())
+    } else {
+        Err(RosettaError::InvalidField { field: format!("{}.{}", path, field), reason: reason.to_string() })
+    }
+}
+
+/// Checks the document-level options of a scene.
+fn validate_options(options: &RenderOptions) -> Result<(), RosettaError> {
+    check(options.width > 0, "options", "width", "must be > 0")?;
+    check(options.height > 0, "options", "height", "must be > 0")?;
+    check(is_css_color(&options.background_color), "options", "background_color", "must be a CSS color such as '#222'")?;
+    check(is_aspect_ratio(&options.preserve_aspect_ratio), "options", "preserve_aspect_ratio", "must be an SVG aspect ratio such as 'xMidYMid meet'")?;
+    if let Some(transform) = &options.transform {
+        check(is_svg_transform(transform), "options", "transform", "must be a list of SVG transforms such as 'translate(100 50) rotate(30)'")?;
+    }
+    if let Some(margin) = options.margin {
+        check(margin.is_finite() && margin >= 0.0, "options", "margin", "must be >= 0")?;
+    }
+    if let Some(step) = options.quantize {
+        check(step.is_finite() && step > 0.0, "options", "quantize", "must be > 0")?;
+    }
+    check(options.segment_length.is_finite() && options.segment_length > 0.0, "options", "segment_length", "must be > 0")?;
+    if let Some(ratio) = options.min_contrast {
+        check((1.0..=21.0).contains(&ratio), "options", "min_contrast", "must be between 1 and 21")?;
+    }
+    #[cfg(feature = "rand")]
+    if let Some(jitter) = &options.color_jitter {
+        let path = "options.color_jitter";
+        check(jitter.hue_range.is_finite() && jitter.hue_range >= 0.0, path, "hue_range", "must be >= 0")?;
+        check((0.0..=1.0).contains(&jitter.opacity_range), path, "opacity_range", "must be between 0 and 1")?;
+    }
+
+    match &options.background {
+        Background::None => Ok(()),
+        Background::Solid(color) => check(is_css_color(color), "options.background", "Solid", "must be a CSS color such as '#222'"),
+        Background::LineGrid(grid) => {
+            let path = "options.background.LineGrid";
+            check(grid.step.is_finite() && grid.step > 0.0, path, "step", "must be > 0")?;
+            check(is_css_color(&grid.color), path, "color", "must be a CSS color such as 'white'")?;
+            check(grid.stroke_width >= 0.0, path, "stroke_width", "must be >= 0")?;
+            check((0.0..=1.0).contains(&grid.opacity), path, "opacity", "must be between 0 and 1")
+        }
+        Background::DotGrid(dots) => {
+            let path = "options.background.DotGrid";
+            check(dots.step > 0, path, "step", "must be > 0")?;
+            check(dots.radius > 0.0, path, "radius", "must be > 0")?;
+            check(is_css_color(&dots.color), path, "color", "must be a CSS color such as 'white'")?;
+            check((0.0..=1.0).contains(&dots.opacity), path, "opacity", "must be between 0 and 1")
+        }
+        Background::Checker(checker) => {
+            let path = "options.background.Checker";
+            check(checker.size > 0, path, "size", "must be > 0")?;
+            check(is_css_color(&checker.color), path, "color", "must be a CSS color such as 'white'")?;
+            check((0.0..=1.0).contains(&checker.opacity), path, "opacity", "must be between 0 and 1")
+        }
+    }
+}
+
+/// Checks that a value is an SVG length or percentage: a number, optionally followed by
+/// a unit such as `px` or `em`, or by `%`.
+fn is_length(value: &str) -> bool {
+    let number = value.trim_end_matches(|c: char| c.is_ascii_alphabetic() || c == '%');
+    let unit = &value[number.len()..];
+    number.parse::<f64>().is_ok_and(f64::is_finite) && (unit.is_empty() || unit == "%" || unit.chars().all(|c| c.is_ascii_alphabetic()))
+}
+
+/// Checks that a value is a `preserveAspectRatio` attribute: `none` or an alignment
+/// such as `xMidYMid`, optionally followed by `meet` or `slice`.
+fn is_aspect_ratio(value: &str) -> bool {
+    let mut words = value.split_whitespace();
+    let align = words.next().is_some_and(|align| {
+        align == "none" || ["xMin", "xMid", "xMax"].iter().any(|x| ["YMin", "YMid", "YMax"].iter().any(|y| align == format!("{}{}", x, y)))
+    });
+    align && words.next().is_none_or(|fit| fit == "meet" || fit == "slice") && words.next().is_none()
+}
+
+/// Checks the geometry, pen and animation of a style found at `path`.
+pub(crate) fn validate_style(style: &RosettaStyle, path: &str) -> Result<(), RosettaError> {
+    let geometry = &style.geometry;
+    let geometry_path = format!("{}.geometry", path);
+    check(geometry.outer_radius.is_finite() && geometry.outer_radius > 0.0, &geometry_path, "outer_radius", "must be > 0")?;
+    check(geometry.inner_radius.is_finite() && geometry.inner_radius > 0.0, &geometry_path, "inner_radius", "must be > 0")?;
+    // An epitrochoid's circle rolls outside the fixed one, whatever their radii.
+    if geometry.kind == CurveKind::Hypotrochoid {
+        check(geometry.inner_radius != geometry.outer_radius, &geometry_path, "inner_radius", "must differ from outer_radius")?;
+    }
+    check(geometry.distance.is_finite() && geometry.distance >= 0.0, &geometry_path, "distance", "must be >= 0")?;
+    check(geometry.steps != Some(0), &geometry_path, "steps", "must be > 0")?;
+
+    let pen = &style.pen;
+    let pen_path = format!("{}.pen", path);
+    check(is_css_color(&pen.color), &pen_path, "color", "must be a CSS color such as 'cyan', '#0ff' or 'var(--curve-color, cyan)'")?;
+    check(pen.width.is_finite() && pen.width > 0.0, &pen_path, "width", "must be > 0")?;
+    check((0.0..=1.0).contains(&pen.opacity), &pen_path, "opacity", "must be between 0 and 1")?;
+    if let Some(dash) = &pen.dash {
+        let mut lengths = dash.split(|c: char| c == ',' || c.is_whitespace()).filter(|length| !length.is_empty()).peekable();
+        let valid = lengths.peek().is_some() && lengths.all(|length| length.parse::<f64>().is_ok_and(|length| length >= 0.0));
+        check(valid, &pen_path, "dash", "must be a list of lengths such as '4 2'")?;
+    }
+    check(pen.segments.as_ref().is_none_or(|colors| !colors.is_empty()), &pen_path, "segments", "must not be empty")?;
+    for (k, color) in pen.segments.iter().flatten().enumerate() {
+        check(is_css_color(color), &pen_path, &format!("segments[{}]", k), "must be a CSS color such as 'cyan' or '#0ff'")?;
+    }
+    if let Some((start, end)) = &pen.gradient {
+        check(is_css_color(start), &pen_path, "gradient.0", "must be a CSS color such as 'cyan' or '#0ff'")?;
+        check(is_css_color(end), &pen_path, "gradient.1", "must be a CSS color such as 'cyan' or '#0ff'")?;
+    }
+
+    let animation = &style.animation;
+    let animation_path = format!("{}.animation", path);
+    check(parse_css_time(&animation.duration).is_some_and(|seconds| seconds > 0.0), &animation_path, "duration", "must be a positive time such as '6s'")?;
+    check(animation.rotate_from_deg.is_finite(), &animation_path, "rotate_from_deg", "must be finite")?;
+    check(animation.rotate_to_deg.is_finite(), &animation_path, "rotate_to_deg", "must be finite")?;
+    check((0.0..=360.0).contains(&animation.phase), &animation_path, "phase", "must be between 0 and 360")?;
+    if let Some(delay) = &animation.delay {
+        check(parse_css_time(delay).is_some(), &animation_path, "delay", "must be a time such as '1.5s'")?;
+    }
+    if let Some(twinkle) = &animation.twinkle {
+        let twinkle_path = format!("{}.twinkle", animation_path);
+        check((0.0..=1.0).contains(&twinkle.min_opacity), &twinkle_path, "min_opacity", "must be between 0 and 1")?;
+        check(parse_css_time(&twinkle.duration).is_some_and(|seconds| seconds > 0.0), &twinkle_path, "duration", "must be a positive time such as '3s'")?;
+        check(parse_css_time(&twinkle.begin).is_some(), &twinkle_path, "begin", "must be a time such as '0s'")?;
+    }
+    Ok(())
+}
+
+/// Builds a scene step by step, adding curves one at a time.
+#[derive(Debug, Clone, Default)]
+pub struct SceneBuilder {
+    scene: Scene,
+}
+
+impl SceneBuilder {
+    /// Sets the size of the canvas, in user units.
+    pub fn canvas(mut self, width: u32, height: u32) -> Self {
+        self.scene.options.width = width;
+        self.scene.options.height = height;
+        self
+    }
+
+    /// Sets the unit of the canvas size, tying user units to a physical length.
+    pub fn units(mut self, units: Units) -> Self {
+        self.scene.options.units = units;
+        self
+    }
+
+    /// Moves, turns or scales the whole composition, e.g. `"translate(-300 300) scale(0.4)"`.
+    pub fn transform(mut self, transform: impl Into<String>) -> Self {
+        self.scene.options.transform = Some(transform.into());
+        self
+    }
+
+    /// Warps every point of the curves with a custom function (see `PointTransform`).
+    pub fn transform_points(mut self, transform: impl Fn(Coordinate) -> Coordinate + Send + Sync + 'static) -> Self {
+        self.scene.options.transform_fn = Some(PointTransform::new(transform));
+        self
+    }
+
+    /// Sets the color filling the canvas.
+    pub fn background(mut self, color: impl Into<String>) -> Self {
+        self.scene.options.background_color = color.into();
+        self
+    }
+
+    /// Draws a line grid over the background color.
+    pub fn grid(self, style: GridStyle) -> Self {
+        self.pattern(Background::LineGrid(style))
+    }
+
+    /// Draws any background pattern over the background color.
+    pub fn pattern(mut self, pattern: Background) -> Self {
+        self.scene.options.background = pattern;
+        self
+    }
+
+    /// Adds a curve on top of the previous ones.
+    /// Accepts a plain style, centered on the scene, as well as a placed curve.
+    pub fn add_curve(mut self, curve: impl Into<SceneCurve>) -> Self {
+        self.scene.curves.push(curve.into());
+        self
+    }
+
+    /// Returns the finished scene.
+    pub fn build(self) -> Scene {
+        self.scene
+    }
+}
+
+/// Selects the curve parameter varied by a sweep.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SweepParam {
+    OuterRadius,
+    InnerRadius,
+    PenOffset,
+}
+
+impl SweepParam {
+    /// Returns the name of the parameter, as shown in captions.
+    fn name(self) -> &'static str {
+        match self {
+            SweepParam::OuterRadius => "outer_radius",
+            SweepParam::InnerRadius => "inner_radius",
+            SweepParam::PenOffset => "pen_offset",
+        }
+    }
+
+    /// Returns a copy of the curve with this parameter set to `value`.
+    fn apply(self, base: &Hypotrochoid, value: f64) -> Hypotrochoid {
+        match self {
+            SweepParam::OuterRadius => base.with_outer_radius(value),
+            SweepParam::InnerRadius => base.with_inner_radius(value),
+            SweepParam::PenOffset => base.with_pen_offset(value),
+        }
+    }
+}
+
+/// Computes the centers of `count` square cells laid out row by row, `columns` per row.
+/// The centers are relative to the center of the whole grid.
+pub fn layout_grid(count: usize, columns: usize, cell_size: f64) -> Vec<Coordinate> {
+    let columns = columns.max(1);
+    let rows = count.div_ceil(columns);
+    let origin_x = (columns as f64 - 1.0) * cell_size / 2.0;
+    let origin_y = (rows as f64 - 1.0) * cell_size / 2.0;
+
+    (0..count)
+        .map(|i| Coordinate {
+            x: (i % columns) as f64 * cell_size - origin_x,
+            y: (i / columns) as f64 * cell_size - origin_y,
+        })
+        .collect()
+}
+
+/// Builds a contact sheet of `base` with one parameter varied over `values`.
+/// Each curve is scaled to fit its own cell and labeled with its parameter value.
+/// The curves rotate about their center, so it's the circle reaching their farthest
+/// point that fits the cell, keeping neighbours apart whatever their angle.
+pub fn sweep(base: &Hypotrochoid, field: SweepParam, values: &[f64]) -> Scene {
+    let options = RenderOptions::default();
+    let columns = (values.len() as f64).sqrt().ceil().max(1.0) as usize;
+    let rows = values.len().div_ceil(columns).max(1);
+    let cell_size = (options.width as f64 / columns as f64)
+        .min(options.height as f64 / rows as f64)
+        / CONTENT_SCALE;
+
+    let curves = values
+        .iter()
+        .zip(layout_grid(values.len(), columns, cell_size))
+        .map(|(&value, offset)| {
+            let curve = field.apply(base, value);
+            let radius = curve.compute_points().iter().map(|p| p.x.hypot(p.y)).fold(0.0, f64::max);
+            SceneCurve {
+                style: StyleRef::Inline(RosettaStyle {
+                    geometry: Geometry {
+                        outer_radius: curve.outer_radius,
+                        inner_radius: curve.inner_radius,
+                        distance: curve.pen_offset,
+                        steps: Some(curve.steps),
+                        kind: curve.kind,
+                    },
+                    ..Default::default()
+                }),
+                offset,
+                scale: if radius > 0.0 { (cell_size / 2.0 - cell_size * 0.15) / radius } else { 1.0 },
+                label: Some(format!("{} = {}", field.name(), value)),
+                text_on_path: None,
+            }
+        })
+        .collect();
+
+    Scene { options, curves }
+}
+
+/// Lowers the number of steps of the curves until the whole scene holds at most
+/// `max_total_points` points, e.g. to keep documents served on the web small.
+///
+/// Named styles are shared with other scenes through the registry, so their curves are
+/// counted but left as they are. Every other curve keeps its two end points, then the rest
+/// of the budget is shared out in proportion to the length of each curve as placed, so long
+/// curves keep more points than small ones; curves already under their share are left as
+/// they are. Budgets leaving fewer than two points to some curve can't be met, and are rejected.
+pub fn apply_point_budget(scene: &mut Scene, registry: &StyleRegistry, max_total_points: usize) -> Result<(), RosettaError> {
+    let total_points = point_count(scene, registry)?;
+    if total_points <= max_total_points {
+        return Ok(());
+    }
+
+    // Curves left to pick their own steps get them pinned, at most to what they'd pick.
+    let sampled: Vec<Option<(usize, f64)>> = scene
+        .curves
+        .iter()
+        .map(|curve| match &curve.style {
+            StyleRef::Inline(style) => placed_curve(&scene.options, curve, style)
+                .ok()
+                .map(|sampled| (sampled.steps(), polyline_length(&sampled.compute_points()) * curve.scale)),
+            StyleRef::Named(_) => None,
+        })
+        .collect();
+    let inline_curves = sampled.iter().flatten().count();
+    let fixed_points = total_points - sampled.iter().flatten().map(|(steps, _)| steps + 1).sum::<usize>();
+    let Some(spare_points) = max_total_points.checked_sub(fixed_points + 2 * inline_curves) else {
+        return Err(RosettaError::InvalidField {
+            field: String::from("max_total_points"),
+            reason: format!("must be at least {}, to keep two points per curve", fixed_points + 2 * inline_curves),
+        });
+    };
+
+    // Rounding the shares down keeps their sum within the spare points.
+    let total_length: f64 = sampled.iter().flatten().map(|(_, length)| length).sum();
+    for (curve, sampled) in scene.curves.iter_mut().zip(sampled) {
+        if let (StyleRef::Inline(style), Some((steps, length))) = (&mut curve.style, sampled) {
+            let share = if total_length > 0.0 { length / total_length } else { 1.0 / inline_curves as f64 };
+            let points = 2 + (spare_points as f64 * share).floor() as usize;
+            style.geometry.steps = Some(steps.min(points - 1));
+        }
+    }
+    debug!("reduced the scene from {} to {} points, for a budget of {}", total_points, point_count(scene, registry)?, max_total_points);
+    Ok(())
+}
+
+/// Counts the points of every curve of the scene, looking named styles up in the registry.
+/// Curves that can't be built don't count, as they can't be rendered either.
+fn point_count(scene: &Scene, registry: &StyleRegistry) -> Result<usize, RosettaError> {
+    let mut count = 0;
+    for curve in &scene.curves {
+        let style = curve.style.resolve(registry)?;
+        count += placed_curve(&scene.options, curve, style).map_or(0, |sampled| sampled.point_count());
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn layout_grid_centers_the_cells() {
+        let centers = layout_grid(5, 3, 10.0);
+        let expected = [(-10.0, -5.0), (0.0, -5.0), (10.0, -5.0), (-10.0, 5.0), (0.0, 5.0)];
+        assert_eq!(centers, expected.map(|(x, y)| Coordinate { x, y }));
+        assert!(layout_grid(0, 3, 10.0).is_empty());
+        assert_eq!(layout_grid(2, 0, 10.0), [Coordinate { x: 0.0, y: -5.0 }, Coordinate { x: 0.0, y: 5.0 }]);
+    }
+
+    #[test]
+    fn swept_curves_keep_apart_as_they_rotate() {
+        let base = Hypotrochoid::new(150.0, 52.5, 100.0, 2000, CurveKind::Hypotrochoid).unwrap();
+        let scene = sweep(&base, SweepParam::PenOffset, &[20.0, 60.0, 100.0, 140.0, 180.0]);
+        assert_eq!(scene.curves.len(), 5);
+        let extents: Vec<(Coordinate, f64)> = scene
+            .curves
+            .iter()
+            .map(|curve| {
+                let StyleRef::Inline(style) = &curve.style else { panic!("sweeps inline their styles") };
+                let points = style.geometry.curve().unwrap().compute_points();
+                (curve.offset, curve.scale * points.iter().map(|p| p.x.hypot(p.y)).fold(0.0, f64::max))
+            })
+            .collect();
+
+        // Three columns of two rows on the square canvas, so the columns set the cell size.
+        let cell_size = 1000.0 / 3.0 / CONTENT_SCALE;
+        for (i, (center, radius)) in extents.iter().enumerate() {
+            assert!((radius - 0.35 * cell_size).abs() < 1e-9, "curve {} reaches {}", i, radius);
+            for (other, other_radius) in &extents[i + 1..] {
+                assert!((center.x - other.x).hypot(center.y - other.y) >= radius + other_radius);
+            }
+        }
+    }
+
+    #[test]
+    fn validate_rejects_empty_segments() {
+        let mut style = RosettaStyle::default();
+        style.pen.segments = Some(Vec::new());
+        let error = Scene::builder().add_curve(style.clone()).build().validate().unwrap_err();
+        assert_eq!(error.to_string(), "curves[0].style.pen.segments must not be empty");
+
+        style.pen.segments = Some(vec![String::from("red")]);
+        assert!(Scene::builder().add_curve(style).build().validate().is_ok());
+    }
+
+    #[test]
+    fn validate_accepts_epitrochoids_with_equal_radii() {
+        let mut style = RosettaStyle::default();
+        style.geometry.inner_radius = style.geometry.outer_radius;
+        let error = Scene::builder().add_curve(style.clone()).build().validate().unwrap_err();
+        assert_eq!(error.to_string(), "curves[0].style.geometry.inner_radius must differ from outer_radius");
+
+        style.geometry.kind = CurveKind::Epitrochoid;
+        assert!(Scene::builder().add_curve(style).build().validate().is_ok());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn malformed_scene_files_name_the_offending_field() {
+        let json = r#"{ "curves": [{ "style": {} }, { "style": { "geometry": { "inner_radius": -5 } } }] }"#;
+        let error = Scene::from_json(json).unwrap_err();
+        assert_eq!(error.to_string(), "curves[1].style.geometry.inner_radius must be > 0");
+
+        let json = r#"{ "curves": [{ "style": { "pen": { "opacity": 2 } }, "scale": 0 }] }"#;
+        assert_eq!(Scene::from_json(json).unwrap_err().to_string(), "curves[0].scale must be > 0");
+
+        let toml = "[options]\nwidth = 0\n";
+        assert_eq!(Scene::from_toml(toml).unwrap_err().to_string(), "options.width must be > 0");
+
+        let toml = "[[curves]]\nstyle = { pen = { opacity = 2.0 } }\n";
+        assert_eq!(Scene::from_toml(toml).unwrap_err().to_string(), "curves[0].style.pen.opacity must be between 0 and 1");
+
+        let toml = "[[curves]]\nstyle = { animation = { duration = \"6 seconds\" } }\n";
+        let error = Scene::from_toml(toml).unwrap_err().to_string();
+        assert!(error.starts_with("curves[0].style.animation.duration "), "{}", error);
+
+        assert!(matches!(Scene::from_json(r#"{ "curves": [ "#), Err(RosettaError::Config(_))));
+        assert!(matches!(Scene::from_toml("[options"), Err(RosettaError::Config(_))));
+    }
+
+    /// Builds a scene using most of the fields a scene file can hold.
+    #[cfg(feature = "serde")]
+    fn sample_scene() -> Scene {
+        let mut style = RosettaStyle::default();
+        style.geometry.steps = None;
+        style.pen.gradient = Some((String::from("gold"), String::from("#f0a")));
+        style.pen.dash = Some(String::from("4 2"));
+        style.animation.delay = Some(String::from("1.5s"));
+        style.animation.twinkle = Some(TwinkleStyle::default());
+        Scene::builder()
+            .canvas(800, 600)
+            .background("#101010")
+            .grid(GridStyle { step: 25.0, ..Default::default() })
+            .add_curve(RosettaStyle::default())
+            .add_curve(SceneCurve {
+                offset: Coordinate { x: -120.5, y: 40.0 },
+                scale: 0.75,
+                label: Some(String::from("tilted")),
+                text_on_path: Some(TextOnPath::default()),
+                ..style.into()
+            })
+            .add_curve(SceneCurve { style: StyleRef::Named(String::from("spiky")), ..RosettaStyle::default().into() })
+            .build()
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn saved_scenes_load_back_equal() {
+        let scene = sample_scene();
+        for extension in ["json", "toml"] {
+            let path = std::env::temp_dir().join(format!("rustigraph_round_trip_{}.{}", std::process::id(), extension));
+            match extension {
+                "json" => scene.save_json(&path).unwrap(),
+                _ => scene.save_toml(&path).unwrap(),
+            }
+            let loaded = Scene::load(&path);
+            std::fs::remove_file(&path).unwrap();
+            assert_eq!(loaded.unwrap(), scene, "the {} file loads back another scene", extension);
+        }
+    }
+
+    #[test]
+    fn content_hashes_stay_the_same() {
+        // Any change of these values invalidates every cache keyed by the hash.
+        assert_eq!(Scene::default().content_hash(), 18348025414275948922);
+        assert_eq!(Scene::builder().add_curve(RosettaStyle::default()).build().content_hash(), 10862273897220946679);
+    }
+
+    #[test]
+    fn content_hash_changes_with_every_field() {
+        let style = RosettaStyle { animation: Animation { twinkle: Some(TwinkleStyle::default()), ..Default::default() }, ..Default::default() };
+        let base = Scene::builder()
+            .add_curve(SceneCurve { label: Some(String::from("a")), text_on_path: Some(TextOnPath::default()), ..style.into() })
+            .build();
+        fn inline_style(scene: &mut Scene) -> &mut RosettaStyle {
+            match &mut scene.curves[0].style {
+                StyleRef::Inline(style) => style,
+                StyleRef::Named(_) => unreachable!("the curve has an inline style"),
+            }
+        }
+        let changes: Vec<fn(&mut Scene)> = vec![
+            |scene| scene.options.width += 1,
+            |scene| scene.options.height += 1,
+            |scene| scene.options.preserve_aspect_ratio = String::from("none"),
+            |scene| scene.options.background_color = String::from("#000"),
+            |scene| scene.options.background = Background::None,
+            |scene| scene.options.background = Background::Solid(String::from("white")),
+            |scene| scene.options.background = Background::LineGrid(GridStyle { step: 10.0, ..Default::default() }),
+            |scene| scene.options.background = Background::LineGrid(GridStyle { opacity: 0.5, ..Default::default() }),
+            |scene| scene.options.background = Background::DotGrid(DotStyle::default()),
+            |scene| scene.options.background = Background::DotGrid(DotStyle { radius: 2.0, ..Default::default() }),
+            |scene| scene.options.background = Background::Checker(CheckerStyle::default()),
+            |scene| scene.options.background = Background::Checker(CheckerStyle { size: 10, ..Default::default() }),
+            |scene| scene.options.minify = true,
+            |scene| scene.options.min_contrast = Some(3.0),
+            |scene| scene.options.units = Units::Millimeters,
+            |scene| scene.options.transform = Some(String::from("rotate(30)")),
+            |scene| scene.options.quantize = Some(1.0),
+            |scene| scene.options.standalone = false,
+            |scene| scene.options.margin = Some(10.0),
+            |scene| scene.options.mode = crate::renderer::RenderMode::Static,
+            |scene| scene.options.animation_backend = crate::renderer::AnimationBackend::Css,
+            |scene| scene.options.interactive = true,
+            |scene| scene.options.segment_length = 2.0,
+            #[cfg(feature = "rand")]
+            |scene| scene.options.color_jitter = Some(JitterStyle::default()),
+            |scene| scene.options.transform_fn = Some(PointTransform::new(|p| p)),
+            |scene| scene.curves.push(scene.curves[0].clone()),
+            |scene| scene.curves[0].style = StyleRef::Named(String::from("spiky")),
+            |scene| scene.curves[0].offset.x = -0.0,
+            |scene| scene.curves[0].offset.y = 1.0,
+            |scene| scene.curves[0].scale = 2.0,
+            |scene| scene.curves[0].label = None,
+            |scene| scene.curves[0].text_on_path = None,
+            |scene| scene.curves[0].text_on_path.as_mut().unwrap().text = String::from("hi"),
+            |scene| scene.curves[0].text_on_path.as_mut().unwrap().font_family = String::from("serif"),
+            |scene| scene.curves[0].text_on_path.as_mut().unwrap().font_size = 20.0,
+            |scene| scene.curves[0].text_on_path.as_mut().unwrap().color = String::from("red"),
+            |scene| scene.curves[0].text_on_path.as_mut().unwrap().start_offset = String::from("50%"),
+            |scene| inline_style(scene).geometry.outer_radius = 160.0,
+            |scene| inline_style(scene).geometry.inner_radius = 50.0,
+            |scene| inline_style(scene).geometry.distance = 90.0,
+            |scene| inline_style(scene).geometry.steps = None,
+            |scene| inline_style(scene).geometry.kind = CurveKind::Epitrochoid,
+            |scene| inline_style(scene).pen.color = String::from("gold"),
+            |scene| inline_style(scene).pen.width = 3.0,
+            |scene| inline_style(scene).pen.opacity = 0.5,
+            |scene| inline_style(scene).pen.dash = Some(String::from("4 2")),
+            |scene| inline_style(scene).pen.segments = Some(vec![String::from("red")]),
+            |scene| inline_style(scene).pen.gradient = Some((String::from("red"), String::from("blue"))),
+            |scene| inline_style(scene).pen.smooth = true,
+            |scene| inline_style(scene).animation.duration = String::from("7s"),
+            |scene| inline_style(scene).animation.rotate_from_deg = 10.0,
+            |scene| inline_style(scene).animation.rotate_to_deg = 180.0,
+            |scene| inline_style(scene).animation.alternate = true,
+            |scene| inline_style(scene).animation.delay = Some(String::from("1s")),
+            |scene| inline_style(scene).animation.phase = 90.0,
+            |scene| inline_style(scene).animation.draw_erase = true,
+            |scene| inline_style(scene).animation.chain = true,
+            |scene| inline_style(scene).animation.draw_in = true,
+            |scene| inline_style(scene).animation.twinkle = None,
+            |scene| inline_style(scene).animation.twinkle.as_mut().unwrap().min_opacity = 0.1,
+            |scene| inline_style(scene).animation.twinkle.as_mut().unwrap().duration = String::from("5s"),
+            |scene| inline_style(scene).animation.twinkle.as_mut().unwrap().begin = String::from("1s"),
+        ];
+
+        let mut hashes = HashSet::from([base.content_hash()]);
+        for (i, change) in changes.iter().enumerate() {
+            let mut changed = base.clone();
+            change(&mut changed);
+            assert!(hashes.insert(changed.content_hash()), "change {} leaves the hash of another scene", i);
+        }
+    }
+
+    #[test]
+    fn merged_scenes_keep_their_curves_apart() {
+        let mut registry = StyleRegistry::default();
+        let mut petal = RosettaStyle::default();
+        petal.animation.chain = true;
+        registry.register("petal", petal.clone());
+        let named = || SceneCurve { style: StyleRef::Named(String::from("petal")), ..RosettaStyle::default().into() };
+        let first = Scene::builder().canvas(800, 600).background("#111").add_curve(named()).add_curve(petal.clone()).build();
+        let second = Scene::builder().canvas(600, 900).background("#222").pattern(Background::None).add_curve(named()).add_curve(petal).build();
+
+        let merged = Scene::merge(vec![first.clone(), second.clone()]);
+        assert_eq!((merged.options.width, merged.options.height), (800, 900));
+        assert_eq!((merged.options.background_color.as_str(), &merged.options.background), ("#222", &Background::None));
+        assert_eq!(merged.curves, [first.curves, second.curves].concat());
+        assert_eq!(Scene::merge(Vec::new()), Scene::default());
+
+        // Both scenes use the same style, and would give their curves the same ids on their own.
+        // The `rosettas` groups are styled together by the rainbow cycle, so they share their id.
+        let mut svg = Vec::new();
+        crate::renderer::write_scene(&mut svg, &merged, &registry).unwrap();
+        let svg = String::from_utf8(svg).unwrap();
+        let document = roxmltree::Document::parse(&svg).unwrap();
+        let ids: Vec<&str> = document.descendants().filter_map(|node| node.attribute("id")).filter(|id| *id != "rosettas").collect();
+        assert_eq!(ids.iter().collect::<HashSet<_>>().len(), ids.len(), "{:?}", ids);
+        let begins: Vec<&str> = document.descendants().filter(|node| node.has_tag_name("animate")).filter_map(|node| node.attribute("begin")).collect();
+        assert_eq!(begins, ["0s", "draw_0.end", "draw_1.end", "draw_2.end"]);
+    }
+
+    #[test]
+    fn point_budget_bounds_the_total() {
+        let mut registry = StyleRegistry::default();
+        registry.register("spiky", RosettaStyle::default());
+        let scene = Scene::builder()
+            .add_curve(RosettaStyle::default())
+            .add_curve(SceneCurve { scale: 2.0, ..crate::renderer::default_styles()[1].clone().into() })
+            .add_curve(SceneCurve { style: StyleRef::Named(String::from("spiky")), ..RosettaStyle::default().into() })
+            .build();
+        let named_points = 3001;
+        for budget in [named_points + 4, named_points + 5, 3500, 4000, 5999] {
+            let mut budgeted = scene.clone();
+            apply_point_budget(&mut budgeted, &registry, budget).unwrap();
+            let total = point_count(&budgeted, &registry).unwrap();
+            assert!(total <= budget, "{} points for a budget of {}", total, budget);
+        }
+
+        let three = Scene::builder().add_curve(RosettaStyle::default()).add_curve(RosettaStyle::default()).add_curve(RosettaStyle::default()).build();
+        assert!(apply_point_budget(&mut three.clone(), &registry, 3).is_err());
+        let mut budgeted = three.clone();
+        apply_point_budget(&mut budgeted, &registry, 6).unwrap();
+        assert_eq!(point_count(&budgeted, &registry).unwrap(), 6);
+        assert!(apply_point_budget(&mut scene.clone(), &registry, named_points + 3).is_err());
+    }
+}